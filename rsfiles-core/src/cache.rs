@@ -0,0 +1,90 @@
+use crate::FileEntry;
+use std::{fs, path::{Path, PathBuf}, time::SystemTime};
+
+/// Counters of a `ListingCache`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+	pub hits: u64,
+	/// Folders that were not cached or changed since they were
+	pub misses: u64,
+	pub entries: usize,
+	/// Estimated memory held by the cached listings
+	pub bytes: usize,
+}
+
+#[derive(Clone)]
+struct CachedListing {
+	path: PathBuf,
+	files: Vec<FileEntry>,
+	modified: SystemTime,
+	bytes: usize,
+}
+
+/// Listings of recently left folders, the least recently used one is dropped
+/// first once there are more than `max_entries` or they take more than `max_bytes`
+#[derive(Clone)]
+pub struct ListingCache {
+	// Most recently used last
+	entries: Vec<CachedListing>,
+	max_entries: usize,
+	max_bytes: usize,
+	stats: CacheStats,
+}
+
+impl ListingCache {
+	pub fn new(max_entries: usize, max_bytes: usize) -> Self {
+		Self {
+			entries: Vec::new(),
+			max_entries,
+			max_bytes,
+			stats: CacheStats::default(),
+		}
+	}
+
+	/// Keeps the listing of `path`, `modified` is the folder's mtime from before it was read
+	pub fn insert(&mut self, path: PathBuf, files: Vec<FileEntry>, modified: SystemTime) {
+		self.remove(&path);
+
+		let bytes = files.iter().map(FileEntry::memory_size).sum();
+		if bytes > self.max_bytes {
+			return;
+		}
+		self.stats.bytes += bytes;
+		self.entries.push(CachedListing { path, files, modified, bytes });
+
+		while self.entries.len() > self.max_entries || self.stats.bytes > self.max_bytes {
+			let oldest = self.entries.remove(0);
+			self.stats.bytes -= oldest.bytes;
+		}
+		self.stats.entries = self.entries.len();
+	}
+
+	/// Takes the listing of `path` out of the cache, as long as the folder's mtime did
+	/// not change since. Changes to files inside it don't touch the mtime, so the
+	/// listing should still be read again in the background.
+	pub fn take(&mut self, path: &Path) -> Option<(Vec<FileEntry>, SystemTime)> {
+		let current = fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+		match self.remove(path) {
+			Some(cached) if Some(cached.modified) == current => {
+				self.stats.hits += 1;
+				Some((cached.files, cached.modified))
+			}
+			_ => {
+				self.stats.misses += 1;
+				None
+			}
+		}
+	}
+
+	fn remove(&mut self, path: &Path) -> Option<CachedListing> {
+		let index = self.entries.iter().position(|cached| cached.path == path)?;
+		let cached = self.entries.remove(index);
+		self.stats.bytes -= cached.bytes;
+		self.stats.entries = self.entries.len();
+		Some(cached)
+	}
+
+	pub fn stats(&self) -> CacheStats {
+		self.stats
+	}
+}
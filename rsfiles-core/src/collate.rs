@@ -0,0 +1,109 @@
+use std::cmp::Ordering;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// How file names are compared with each other
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Collation {
+	/// Digit runs compare as numbers, case and accents only break ties (`file2` < `file10`)
+	#[default]
+	Natural,
+	/// Plain byte order of the UTF-8 names (`File10` < `file2`)
+	Bytes,
+}
+
+impl Collation {
+	pub fn compare(self, a: &str, b: &str) -> Ordering {
+		match self {
+			Collation::Natural => natural_cmp(a, b),
+			Collation::Bytes => a.as_bytes().cmp(b.as_bytes()),
+		}
+	}
+}
+
+// Every level of a key ends with this, below the first byte of any token
+const LEVEL_END: u8 = 0;
+// First bytes of the tokens, in the order the tokens sort in: characters below '0'
+// are that character plus one, then come numbers, then other ASCII characters as the
+// character plus two and last the rest with this byte and their code point after it
+const NUMBER: u8 = b'0' + 1;
+const WIDE_CHAR: u8 = 0x80 + 2;
+
+/// A name taken apart once for sorting, as bytes that compare the way `natural_cmp`
+/// compares the names. Entries keep one for their name and one for their extension.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct NaturalKey(Box<[u8]>);
+
+impl NaturalKey {
+	pub(crate) fn new(name: &str) -> Self {
+		let mut key = Vec::with_capacity(name.len() * 3 + 3);
+		for level in [Level::Base, Level::Accents, Level::Case] {
+			push_tokens(&mut key, name, level);
+			key.push(LEVEL_END);
+		}
+		// Only leading zeros differ (`1` and `01`), fall back to bytes to stay consistent
+		key.extend_from_slice(name.as_bytes());
+		Self(key.into_boxed_slice())
+	}
+
+	pub(crate) fn len(&self) -> usize {
+		self.0.len()
+	}
+}
+
+/// Strength of the comparison, like the levels of the Unicode collation algorithm
+#[derive(Clone, Copy)]
+enum Level {
+	/// Ignores case and accents
+	Base,
+	/// Ignores case
+	Accents,
+	/// Exact characters
+	Case,
+}
+
+/// Adds the tokens of `name` at `level` to `key`, every one either a single character
+/// or a whole run of ASCII digits. A run compares as the number it is: its length without
+/// leading zeros goes first, so a longer number is the larger one.
+fn push_tokens(key: &mut Vec<u8>, name: &str, level: Level) {
+	let chars: Vec<char> = match level {
+		Level::Base => name.nfd()
+			.filter(|&c| !is_combining_mark(c))
+			.flat_map(char::to_lowercase)
+			.collect(),
+		Level::Accents => name.nfd().flat_map(char::to_lowercase).collect(),
+		Level::Case => name.nfd().collect(),
+	};
+
+	let mut i = 0;
+	while i < chars.len() {
+		let c = chars[i];
+		if c.is_ascii_digit() {
+			let start = i;
+			while i < chars.len() && chars[i].is_ascii_digit() {
+				i += 1;
+			}
+			let digits: String = chars[start..i].iter().collect();
+			let digits = digits.trim_start_matches('0');
+			key.push(NUMBER);
+			key.extend_from_slice(&(digits.len() as u32).to_be_bytes());
+			key.extend_from_slice(digits.as_bytes());
+			continue;
+		}
+		if c < '0' {
+			key.push(c as u8 + 1);
+		} else if c.is_ascii() {
+			key.push(c as u8 + 2);
+		} else {
+			key.push(WIDE_CHAR);
+			key.extend_from_slice(&(c as u32).to_be_bytes()[1..]);
+		}
+		i += 1;
+	}
+}
+
+/// Compares names the way people read them: `file2` < `file10`, `e` < `é` < `f`
+/// and case only matters when the names are otherwise equal. Entries build a key
+/// for their name once instead, see `NaturalKey`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+	NaturalKey::new(a).cmp(&NaturalKey::new(b))
+}
@@ -0,0 +1,93 @@
+use crate::{read_file_entry, FileEntry};
+use std::{collections::HashMap, path::{Path, PathBuf}};
+
+/// What to do with an entry whose name is already taken at the destination
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictAction {
+	/// The existing entry is replaced, it is only removed once the new one is in place
+	Replace,
+	Skip,
+	/// The new entry gets a free name like "name (1).ext"
+	KeepBoth,
+	/// Folders are combined, files that exist in both are replaced.
+	/// Anything but two folders is replaced instead.
+	Merge,
+}
+
+/// An entry that would end up on an existing one
+#[derive(Clone, Debug)]
+pub struct Conflict {
+	pub source: FileEntry,
+	pub existing: FileEntry,
+}
+
+impl Conflict {
+	/// Merging only makes sense for two folders
+	pub fn can_merge(&self) -> bool {
+		self.source.is_dir() && self.existing.is_dir()
+	}
+
+	/// Pasting into the folder the entry is in already, only a copy under another name works
+	pub fn is_same_entry(&self) -> bool {
+		self.source.as_path() == self.existing.as_path()
+	}
+}
+
+/// Decisions for the conflicts of one operation, by source path
+#[derive(Clone, Debug, Default)]
+pub struct ConflictPolicy {
+	/// Used for every conflict without a decision of its own
+	pub default: Option<ConflictAction>,
+	decisions: HashMap<PathBuf, ConflictAction>,
+}
+
+impl ConflictPolicy {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// The same action for every conflict, for batch operations that can't ask
+	pub fn all(action: ConflictAction) -> Self {
+		Self { default: Some(action), decisions: HashMap::new() }
+	}
+
+	pub fn decide(&mut self, source: PathBuf, action: ConflictAction) {
+		self.decisions.insert(source, action);
+	}
+
+	/// What happens to `source` if its target exists, `None` when nobody decided
+	pub fn action(&self, source: &Path) -> Option<ConflictAction> {
+		self.decisions.get(source).copied().or(self.default)
+	}
+}
+
+/// Sources whose name is already taken in `dest`
+pub fn find_conflicts(sources: &[PathBuf], dest: &Path) -> Vec<Conflict> {
+	sources.iter()
+		.filter_map(|source| {
+			let existing = read_file_entry(&target_path(source, dest))?;
+			let source = read_file_entry(source)?;
+			Some(Conflict { source, existing })
+		})
+		.collect()
+}
+
+/// Where a source ends up inside `dest`
+pub fn target_path(source: &Path, dest: &Path) -> PathBuf {
+	dest.join(source.file_name().unwrap_or(source.as_os_str()))
+}
+
+/// First free name next to `path` of the form "name (1).ext", "name (2).ext" ...
+pub fn unique_name(path: &Path) -> PathBuf {
+	let name = path.file_name().unwrap_or_default().to_string_lossy();
+	// Folders and dot files keep their whole name, "v1.2" becomes "v1.2 (1)"
+	let (base, extension) = match name.rfind('.') {
+		Some(dot) if dot > 0 && !path.is_dir() => name.split_at(dot),
+		_ => (&*name, ""),
+	};
+
+	(1..)
+		.map(|n| path.with_file_name(format!("{} ({}){}", base, n, extension)))
+		.find(|candidate| candidate.symlink_metadata().is_err())
+		.unwrap()
+}
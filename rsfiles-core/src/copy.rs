@@ -0,0 +1,181 @@
+use crate::platform;
+use std::{
+	fs::{self, File, Metadata},
+	io::{self, Read, Seek, SeekFrom, Write},
+	path::Path,
+	thread,
+};
+
+// Files are copied in chunks of this size, pausing and cancelling take effect between them
+pub(crate) const CHUNK_SIZE: usize = 1024 * 1024;
+// More threads than this only wait on the same disk
+const MAX_WORKERS: usize = 8;
+
+/// What a copy keeps of the original besides its contents
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CopyOptions {
+	/// Mode bits, the read-only attribute on Windows
+	pub permissions: bool,
+	/// Modified and accessed times, of folders too
+	pub times: bool,
+	/// Links are recreated as links, otherwise what they point to is copied
+	pub symlinks: bool,
+	/// Extended attributes, on Linux only
+	pub xattrs: bool,
+	/// Holes in sparse files stay holes instead of being written out as zeros
+	pub sparse: bool,
+	/// Owner and group, changing those needs root so it is off by default
+	pub ownership: bool,
+	/// Lets the kernel copy without the data passing through rsfiles: blocks are shared
+	/// on btrfs and XFS (reflinks), `copy_file_range` does it elsewhere on Linux
+	pub kernel_copy: bool,
+	/// Files smaller than one chunk are copied by this many threads at once, 1 copies
+	/// everything one after the other
+	pub workers: usize,
+	/// Reads every copied file back and compares its BLAKE3 hash with the original's,
+	/// a copy that does not match is reported and removed
+	pub verify: bool,
+}
+
+impl Default for CopyOptions {
+	fn default() -> Self {
+		Self {
+			permissions: true,
+			times: true,
+			symlinks: true,
+			xattrs: true,
+			sparse: true,
+			ownership: false,
+			kernel_copy: true,
+			workers: thread::available_parallelism().map_or(1, |n| n.get().min(MAX_WORKERS)),
+			verify: false,
+		}
+	}
+}
+
+/// Gives the copy `target` what `options` keeps of `source`. Has to run after the
+/// contents are in place, writing into a folder changes its modified time and a
+/// read-only mode would keep the rest from being set.
+pub(crate) fn apply_metadata(source: &Path, metadata: &Metadata, target: &Path, options: &CopyOptions) -> io::Result<()> {
+	if options.xattrs {
+		match platform::read_xattrs(source).and_then(|attributes| platform::write_xattrs(target, &attributes)) {
+			Err(e) if e.kind() != io::ErrorKind::Unsupported => return Err(e),
+			_ => {}
+		}
+	}
+	if options.ownership {
+		platform::copy_ownership(metadata, target)?;
+	}
+	// There is no portable way to set the times or mode of a link itself
+	if metadata.is_symlink() {
+		return Ok(());
+	}
+	if options.times {
+		platform::set_file_times(target, metadata.accessed().ok(), metadata.modified().ok())?;
+	}
+	if options.permissions {
+		fs::set_permissions(target, metadata.permissions())?;
+	}
+	Ok(())
+}
+
+/// Copies what is left of `reader` into `writer`, the fastest way that works for the
+/// two files. `on_chunk` gets `writer` and the bytes of every chunk and stops the copy
+/// by returning false, then `Ok(false)` comes back. Runs of zeros in a `sparse` file are skipped
+/// over so they stay holes in the copy.
+pub(crate) fn copy_contents(
+	reader: &mut File,
+	writer: &mut File,
+	options: &CopyOptions,
+	sparse: bool,
+	mut on_chunk: impl FnMut(&File, u64) -> bool,
+) -> io::Result<bool> {
+	if options.kernel_copy {
+		// A clone is always of the whole file
+		let cloned = match reader.stream_position()? {
+			0 => platform::clone_file(reader, writer),
+			_ => Err(io::ErrorKind::Unsupported.into()),
+		};
+		match cloned {
+			Ok(()) => return Ok(on_chunk(writer, reader.metadata()?.len())),
+			Err(e) if e.kind() != io::ErrorKind::Unsupported => return Err(e),
+			Err(_) => {}
+		}
+		// The kernel does not know about holes, those need the loop below
+		if !sparse {
+			loop {
+				match platform::copy_file_range(reader, writer, CHUNK_SIZE) {
+					Ok(0) => return Ok(true),
+					Ok(copied) => {
+						if !on_chunk(writer, copied as u64) {
+							return Ok(false);
+						}
+					}
+					Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+					// Both files are where the kernel stopped, the loop below goes on from there
+					Err(e) if e.kind() == io::ErrorKind::Unsupported => break,
+					Err(e) => return Err(e),
+				}
+			}
+		}
+	}
+
+	let mut buffer = vec![0; CHUNK_SIZE];
+	loop {
+		let read = match reader.read(&mut buffer) {
+			Ok(0) => break,
+			Ok(read) => read,
+			Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(e) => return Err(e),
+		};
+		if sparse && buffer[..read].iter().all(|&byte| byte == 0) {
+			writer.seek(SeekFrom::Current(read as i64))?;
+		} else {
+			writer.write_all(&buffer[..read])?;
+		}
+		if !on_chunk(writer, read as u64) {
+			return Ok(false);
+		}
+	}
+	// A hole at the end is only there once the length is set
+	if sparse {
+		let length = writer.stream_position()?;
+		writer.set_len(length)?;
+	}
+	Ok(true)
+}
+
+/// Checks that `target` holds the same bytes as `source` by hashing both. The copy is
+/// read from the disk where the platform allows, not from what is still cached of it.
+/// `proceed` is asked between chunks and stops the check by returning false, then
+/// `Ok(false)` comes back.
+pub(crate) fn verify_contents(source: &Path, target: &Path, mut proceed: impl FnMut() -> bool) -> io::Result<bool> {
+	let Some(original) = hash_file(&mut File::open(source)?, &mut proceed)? else { return Ok(false) };
+	let mut copy = File::open(target)?;
+	platform::drop_cached(&copy);
+	let Some(copied) = hash_file(&mut copy, &mut proceed)? else { return Ok(false) };
+	if original != copied {
+		return Err(io::Error::other("The copy does not match the original, their checksums differ"));
+	}
+	Ok(true)
+}
+
+/// BLAKE3 hash of what is left of `file`, `None` once `proceed` says to stop
+fn hash_file(file: &mut File, proceed: &mut impl FnMut() -> bool) -> io::Result<Option<blake3::Hash>> {
+	let mut hasher = blake3::Hasher::new();
+	let mut buffer = vec![0; CHUNK_SIZE];
+	loop {
+		match file.read(&mut buffer) {
+			Ok(0) => break,
+			Ok(read) => {
+				hasher.update(&buffer[..read]);
+			}
+			Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(e) => return Err(e),
+		}
+		if !proceed() {
+			return Ok(None);
+		}
+	}
+	Ok(Some(hasher.finalize()))
+}
@@ -0,0 +1,83 @@
+use crate::{platform, CancelToken};
+use std::{collections::{HashMap, HashSet}, path::{Path, PathBuf}, time::SystemTime};
+use walkdir::WalkDir;
+
+/// Total size of everything below a directory
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DirSize {
+	pub bytes: u64,
+	pub files: u64,
+	pub dirs: u64,
+	/// Entries that could not be read, the total is a lower bound when this is not 0
+	pub errors: u64,
+}
+
+impl DirSize {
+	pub fn is_partial(&self) -> bool {
+		self.errors > 0
+	}
+}
+
+/// Walks the directory and adds up the size of all files below it.
+/// Symbolic links are counted as links and never followed, so link loops can not
+/// trap the walk, and a file with several hard links is only counted once.
+/// Returns `None` once `cancel` is set.
+pub fn directory_size(path: &Path, cancel: &CancelToken) -> Option<DirSize> {
+	let mut size = DirSize::default();
+	let mut seen_links = HashSet::new();
+
+	for entry in WalkDir::new(path).min_depth(1).follow_links(false) {
+		if cancel.is_cancelled() {
+			return None;
+		}
+
+		let metadata = match entry.and_then(|entry| entry.metadata()) {
+			Ok(metadata) => metadata,
+			Err(_) => {
+				size.errors += 1;
+				continue;
+			}
+		};
+
+		if metadata.is_dir() {
+			size.dirs += 1;
+			continue;
+		}
+		if let Some(id) = platform::hardlink_id(&metadata)
+			&& !seen_links.insert(id) {
+			continue;
+		}
+		size.files += 1;
+		size.bytes += metadata.len();
+	}
+
+	Some(size)
+}
+
+/// Finished directory sizes, an entry is only valid while the directory's mtime is unchanged.
+/// The mtime only moves when direct children are added, removed or renamed, changes
+/// deeper down are not noticed until the cache is cleared.
+#[derive(Clone, Debug, Default)]
+pub struct SizeCache {
+	entries: HashMap<PathBuf, (SystemTime, DirSize)>,
+}
+
+impl SizeCache {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn get(&self, path: &Path, modified: SystemTime) -> Option<DirSize> {
+		self.entries.get(path)
+			.filter(|(cached, _)| *cached == modified)
+			.map(|(_, size)| *size)
+	}
+
+	pub fn insert(&mut self, path: PathBuf, modified: SystemTime, size: DirSize) {
+		self.entries.insert(path, (modified, size));
+	}
+
+	pub fn clear(&mut self) {
+		self.entries.clear();
+	}
+}
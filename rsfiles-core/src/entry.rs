@@ -0,0 +1,247 @@
+use crate::collate::NaturalKey;
+use crate::DirSize;
+use std::{collections::HashSet, fs, path::Path, path::PathBuf, time::SystemTime};
+
+// Same limit Linux uses before giving up with ELOOP
+const MAX_LINK_HOPS: usize = 40;
+
+/// Whether the target of a symbolic link can be reached
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkState {
+	Ok,
+	/// The target does not exist (anymore)
+	Broken,
+	/// Following the link leads back to itself
+	Loop,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymlinkInfo {
+	/// Target exactly as stored in the link, may be relative to the link's folder
+	pub target: PathBuf,
+	pub state: LinkState,
+}
+
+impl SymlinkInfo {
+	/// Reads the symbolic link at `path` without following it
+	pub fn read(path: &Path) -> std::io::Result<Self> {
+		let target = fs::read_link(path)?;
+		let state = if fs::metadata(path).is_ok() {
+			LinkState::Ok
+		} else if is_link_loop(path) {
+			LinkState::Loop
+		} else {
+			LinkState::Broken
+		};
+		Ok(Self { target, state })
+	}
+}
+
+/// Follows the chain of links starting at `path` and checks whether it comes back around
+fn is_link_loop(path: &Path) -> bool {
+	let mut visited = HashSet::new();
+	let mut current = path.to_path_buf();
+
+	while let Ok(target) = fs::read_link(&current) {
+		current = current.parent().unwrap_or(Path::new("")).join(target);
+		if !visited.insert(current.clone()) || visited.len() > MAX_LINK_HOPS {
+			return true;
+		}
+	}
+	false
+}
+
+/// What kind of file system object an entry is, for links this is the kind of the target
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FileKind {
+	File,
+	Dir,
+	/// Symbolic link whose target can not be followed
+	Symlink,
+	Fifo,
+	Socket,
+	CharDevice,
+	BlockDevice,
+	Unknown,
+}
+
+impl From<fs::FileType> for FileKind {
+	fn from(ty: fs::FileType) -> Self {
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::FileTypeExt;
+			if ty.is_fifo() {
+				return FileKind::Fifo;
+			} else if ty.is_socket() {
+				return FileKind::Socket;
+			} else if ty.is_char_device() {
+				return FileKind::CharDevice;
+			} else if ty.is_block_device() {
+				return FileKind::BlockDevice;
+			}
+		}
+
+		if ty.is_dir() {
+			FileKind::Dir
+		} else if ty.is_file() {
+			FileKind::File
+		} else if ty.is_symlink() {
+			FileKind::Symlink
+		} else {
+			FileKind::Unknown
+		}
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct FileEntry {
+	path: PathBuf,
+	display_name: String,
+	kind: FileKind,
+	len: u64,
+	modified: Option<SystemTime>,
+	created: Option<SystemTime>,
+	accessed: Option<SystemTime>,
+	permissions: Option<fs::Permissions>,
+	is_hidden: bool,
+	link: Option<SymlinkInfo>,
+	error: Option<String>,
+	// Filled in later by a background walk, see `directory_size`
+	dir_size: Option<DirSize>,
+	// Built once with the entry, sorting compares these instead of the names
+	name_key: NaturalKey,
+	extension_key: NaturalKey,
+}
+#[allow(dead_code)]
+impl FileEntry {
+	/// Entry described by `metadata`, for links that can be followed it should be the target's
+	pub fn from_metadata(
+		path: PathBuf,
+		display_name: String,
+		metadata: &fs::Metadata,
+		is_hidden: bool,
+		link: Option<SymlinkInfo>,
+	) -> Self {
+		Self{
+			path,
+			name_key: NaturalKey::new(&display_name),
+			extension_key: NaturalKey::new(name_extension(&display_name)),
+			display_name,
+			kind: metadata.file_type().into(),
+			len: metadata.len(),
+			modified: metadata.modified().ok(),
+			created: metadata.created().ok(),
+			accessed: metadata.accessed().ok(),
+			permissions: Some(metadata.permissions()),
+			is_hidden,
+			link,
+			error: None,
+			dir_size: None,
+		}
+	}
+
+	/// Entry whose metadata could not be read, `error` says why
+	pub fn unreadable(
+		path: PathBuf,
+		display_name: String,
+		kind: FileKind,
+		is_hidden: bool,
+		error: String,
+	) -> Self {
+		Self{
+			path,
+			name_key: NaturalKey::new(&display_name),
+			extension_key: NaturalKey::new(name_extension(&display_name)),
+			display_name,
+			kind,
+			len: 0,
+			modified: None,
+			created: None,
+			accessed: None,
+			permissions: None,
+			is_hidden,
+			link: None,
+			error: Some(error),
+			dir_size: None,
+		}
+	}
+
+	pub fn path(&self) -> PathBuf { self.path.clone() }
+	pub fn as_path(&self) -> &Path { &self.path }
+	pub fn display_name(&self) -> &str { &self.display_name }
+	/// Extension of the shown name, the type column sorts by it
+	pub(crate) fn name_extension(&self) -> &str { name_extension(&self.display_name) }
+	pub(crate) fn name_key(&self) -> &NaturalKey { &self.name_key }
+	pub(crate) fn extension_key(&self) -> &NaturalKey { &self.extension_key }
+	pub fn kind(&self) -> FileKind { self.kind }
+	pub fn is_dir(&self) -> bool { self.kind == FileKind::Dir }
+	pub fn is_shortcut(&self) -> bool { self.path.is_shortcut() }
+	pub fn is_symlink(&self) -> bool { self.link.is_some() }
+	pub fn link(&self) -> Option<&SymlinkInfo> { self.link.as_ref() }
+	pub fn is_unreadable(&self) -> bool { self.error.is_some() }
+	pub fn error(&self) -> Option<&str> { self.error.as_deref() }
+	/// Size in bytes, for links that can be followed the size of the target
+	/// and for directories the total of their contents once it is known
+	pub fn size(&self) -> u64 { self.dir_size.map_or(self.len, |size| size.bytes) }
+	pub fn dir_size(&self) -> Option<DirSize> { self.dir_size }
+	pub fn set_dir_size(&mut self, size: Option<DirSize>) { self.dir_size = size; }
+	pub fn modified(&self) -> Option<SystemTime> { self.modified }
+	pub fn created(&self) -> Option<SystemTime> { self.created }
+	pub fn accessed(&self) -> Option<SystemTime> { self.accessed }
+	pub fn permissions(&self) -> Option<&fs::Permissions> { self.permissions.as_ref() }
+	pub fn is_readonly(&self) -> bool { self.permissions.as_ref().is_some_and(|p| p.readonly()) }
+	pub fn is_hidden(&self) -> bool { self.is_hidden }
+	/// Rough number of bytes the entry takes in memory, strings included
+	pub fn memory_size(&self) -> usize {
+		std::mem::size_of::<Self>()
+			+ self.path.as_os_str().len()
+			+ self.display_name.len()
+			+ self.name_key.len()
+			+ self.extension_key.len()
+			+ self.link.as_ref().map_or(0, |link| link.target.as_os_str().len())
+			+ self.error.as_ref().map_or(0, String::len)
+	}
+	pub fn extension(&self) -> String { 
+	self.path
+		.extension()
+		.and_then(|ext| ext.to_str())
+		.unwrap_or_default()
+		.to_lowercase()
+		.to_string()
+	}
+}
+
+fn name_extension(name: &str) -> &str {
+	Path::new(name)
+		.extension()
+		.and_then(|ext| ext.to_str())
+		.unwrap_or_default()
+}
+
+pub trait PathExt {
+	/// Windows `.lnk` file
+	fn is_shortcut(&self) -> bool;
+	/// Either a `.lnk` file or a symbolic link
+	fn is_link(&self) -> bool;
+	/// Where a symbolic link points to, canonicalized when the target exists
+	fn symlink_target(&self) -> Option<PathBuf>;
+}
+
+impl PathExt for PathBuf {
+	fn is_shortcut(&self) -> bool {
+		self.extension()
+			.and_then(|ext| ext.to_str())
+			.map(|ext| ext.eq_ignore_ascii_case("lnk"))
+			.unwrap_or(false)
+	}
+
+	fn is_link(&self) -> bool {
+		self.is_symlink() || self.is_shortcut()
+	}
+
+	fn symlink_target(&self) -> Option<PathBuf> {
+		let target = fs::read_link(self).ok()?;
+		let target = self.parent().unwrap_or(Path::new("")).join(target);
+		Some(fs::canonicalize(&target).unwrap_or(target))
+	}
+}
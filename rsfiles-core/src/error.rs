@@ -0,0 +1,82 @@
+use std::{fmt, io, path::PathBuf};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Every failure the engine can report, with the path it happened on
+#[derive(Debug)]
+pub enum Error {
+	/// The directory itself (or one of its entries) could not be read
+	ReadDir(PathBuf, io::Error),
+	/// Metadata of a single entry could not be read
+	Metadata(PathBuf, io::Error),
+	/// Copying a file or folder failed
+	Copy(PathBuf, io::Error),
+	/// Moving (renaming) a file or folder failed
+	Move(PathBuf, io::Error),
+	/// Deleting failed, even after trying with elevated privileges
+	Delete(PathBuf, String),
+	/// A `.lnk` file could not be read or is not a valid shell link
+	Shortcut(PathBuf, io::Error),
+	/// Change notifications for a directory could not be set up
+	Watch(PathBuf, String),
+	/// A folder was to be copied or moved into itself or one of its subfolders
+	IntoItself(PathBuf),
+}
+
+impl Error {
+	/// The path the failed operation was working on
+	pub fn path(&self) -> &PathBuf {
+		match self {
+			Error::ReadDir(path, _)
+			| Error::Metadata(path, _)
+			| Error::Copy(path, _)
+			| Error::Move(path, _)
+			| Error::Delete(path, _)
+			| Error::Shortcut(path, _)
+			| Error::Watch(path, _)
+			| Error::IntoItself(path) => path,
+		}
+	}
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Error::ReadDir(_, e) => write!(f, "Error reading directory: {}", e),
+			Error::Metadata(path, e) => write!(
+				f,
+				"Error reading metadata for {}: {}",
+				path.file_name().unwrap_or_default().to_string_lossy(),
+				e
+			),
+			Error::Copy(_, e) => write!(f, "Error copying file: {}", e),
+			Error::Move(_, e) => write!(f, "Error moving file: {}", e),
+			Error::Delete(path, e) => write!(
+				f,
+				"Error deleting {}: {}",
+				if path.is_dir() { "folder" } else { "file" },
+				e
+			),
+			Error::Shortcut(_, e) => write!(f, "Could not resolve shortcut: {}", e),
+			Error::Watch(_, e) => write!(f, "Could not watch directory for changes: {}", e),
+			Error::IntoItself(path) => write!(
+				f,
+				"Cannot copy or move {} into itself",
+				path.file_name().unwrap_or_default().to_string_lossy()
+			),
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Error::ReadDir(_, e)
+			| Error::Metadata(_, e)
+			| Error::Copy(_, e)
+			| Error::Move(_, e)
+			| Error::Shortcut(_, e) => Some(e),
+			Error::Delete(_, _) | Error::Watch(_, _) | Error::IntoItself(_) => None,
+		}
+	}
+}
@@ -0,0 +1,152 @@
+use crate::platform;
+use std::time::SystemTime;
+
+pub fn format_size(bytes: u64) -> String {
+	const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+	const UNIT_SIZE: f64 = 1024.0;
+
+	if bytes == 0 {
+		return "0 B".to_string();
+	}
+
+	let i = (bytes as f64).log(UNIT_SIZE).floor() as usize;
+	let size = bytes as f64 / UNIT_SIZE.powi(i as i32);
+
+	if i < UNITS.len() {
+		format!("{:.1} {}", size, UNITS[i])
+	} else {
+		let tb_size = bytes as f64 / UNIT_SIZE.powi(4);
+		format!("{:.1} TB", tb_size)
+	}
+}
+
+pub fn format_time_ago(time: SystemTime) -> String {
+	let now = SystemTime::now();
+	let duration = now.duration_since(time).unwrap_or_default();
+	let secs = duration.as_secs();
+
+	let years = secs / 31_536_000;
+	let days = (secs % 31_536_000) / 86400;
+	let hours = (secs % 86400) / 3600;
+	let minutes = (secs % 3600) / 60;
+
+	if years > 0 {
+		format!("{}y {}d ago", years, days)
+	} else if days > 0 {
+		format!("{}d {}h ago", days, hours)
+	} else if hours > 0 {
+		format!("{}h {}m ago", hours, minutes)
+	} else if minutes > 0 {
+		format!("{}m ago", minutes)
+	} else {
+		"Just now".to_string()
+	}
+}
+
+/// How timestamps are written in the file list
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeStyle {
+	/// `2024-05-01 13:45`
+	#[default]
+	Iso,
+	/// Short date and time the way the user's locale writes them
+	Locale,
+	/// `3d 4h ago`
+	Relative,
+}
+
+impl TimeStyle {
+	pub const ALL: [TimeStyle; 3] = [TimeStyle::Iso, TimeStyle::Locale, TimeStyle::Relative];
+}
+
+impl std::fmt::Display for TimeStyle {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			TimeStyle::Iso => "ISO 8601",
+			TimeStyle::Locale => "Locale",
+			TimeStyle::Relative => "Relative",
+		})
+	}
+}
+
+/// Broken down local date and time
+struct LocalTime {
+	year: i64,
+	month: u32,
+	day: u32,
+	hour: u32,
+	minute: u32,
+	second: u32,
+	/// Offset from UTC in seconds
+	offset: i64,
+}
+
+impl LocalTime {
+	fn new(time: SystemTime) -> Self {
+		let utc = unix_seconds(time);
+		let offset = platform::utc_offset(utc);
+		let local = utc + offset;
+
+		let (year, month, day) = civil_from_days(local.div_euclid(86400));
+		let secs_of_day = local.rem_euclid(86400);
+		Self {
+			year,
+			month,
+			day,
+			hour: (secs_of_day / 3600) as u32,
+			minute: (secs_of_day % 3600 / 60) as u32,
+			second: (secs_of_day % 60) as u32,
+			offset,
+		}
+	}
+}
+
+/// Seconds since the unix epoch, negative for older times
+fn unix_seconds(time: SystemTime) -> i64 {
+	match time.duration_since(SystemTime::UNIX_EPOCH) {
+		Ok(duration) => duration.as_secs() as i64,
+		Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+	}
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) of the proleptic Gregorian calendar,
+/// based on Howard Hinnant's `civil_from_days`
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+	let z = days + 719_468;
+	let era = z.div_euclid(146_097);
+	let day_of_era = z.rem_euclid(146_097);
+	let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_index = (5 * day_of_year + 2) / 153; // March is 0
+	let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+	let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+	let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+	(year, month, day)
+}
+
+/// Local date and time as `2024-05-01 13:45`
+pub fn format_time(time: SystemTime) -> String {
+	let t = LocalTime::new(time);
+	format!("{:04}-{:02}-{:02} {:02}:{:02}", t.year, t.month, t.day, t.hour, t.minute)
+}
+
+/// Complete ISO 8601 timestamp with seconds and UTC offset, e.g. `2024-05-01T13:45:07+02:00`
+pub fn format_time_full(time: SystemTime) -> String {
+	let t = LocalTime::new(time);
+	let sign = if t.offset < 0 { '-' } else { '+' };
+	let offset = t.offset.abs();
+	format!(
+		"{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}",
+		t.year, t.month, t.day, t.hour, t.minute, t.second,
+		sign, offset / 3600, offset % 3600 / 60
+	)
+}
+
+pub fn format_time_styled(time: SystemTime, style: TimeStyle) -> String {
+	match style {
+		TimeStyle::Iso => format_time(time),
+		TimeStyle::Locale => platform::format_locale_time(unix_seconds(time))
+			.unwrap_or_else(|| format_time(time)),
+		TimeStyle::Relative => format_time_ago(time),
+	}
+}
@@ -0,0 +1,852 @@
+use crate::copy::{apply_metadata, copy_contents, verify_contents, CHUNK_SIZE};
+use crate::journal::{partial_path, Resume, SYNC_INTERVAL};
+use crate::{check_destination, delete_path, platform, target_path, unique_name, CancelToken, ConflictAction, ConflictPolicy, CopyJournal, CopyOptions};
+use std::{
+	fs::{self, File, Metadata, OpenOptions},
+	io::{self, Seek, SeekFrom},
+	path::{Path, PathBuf},
+	sync::{atomic::{AtomicUsize, Ordering}, mpsc, Arc, Condvar, Mutex},
+	thread,
+	time::{Duration, Instant},
+};
+use walkdir::WalkDir;
+
+// Small files wait for the worker threads in batches of this many
+const BATCH_SIZE: usize = 256;
+// Progress is reported at most this often
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A file operation that runs in the background
+#[derive(Clone, Debug)]
+pub enum Job {
+	/// Copies every source into `dest` under its own name, `conflicts` says what
+	/// happens when that name is taken and `options` what the copies keep
+	Copy { sources: Vec<PathBuf>, dest: PathBuf, conflicts: ConflictPolicy, options: CopyOptions },
+	/// Moves every source into `dest` like a copy does. Onto another file system
+	/// it copies with `options`, checks the copy and deletes the source after,
+	/// with `verify` only once every file matches its checksum.
+	Move { sources: Vec<PathBuf>, dest: PathBuf, conflicts: ConflictPolicy, options: CopyOptions },
+	Delete { paths: Vec<PathBuf> },
+}
+
+impl Job {
+	pub fn sources(&self) -> &[PathBuf] {
+		match self {
+			Job::Copy { sources, .. } | Job::Move { sources, .. } => sources,
+			Job::Delete { paths } => paths,
+		}
+	}
+
+	/// Short description like "Copying 3 items to Documents"
+	pub fn description(&self) -> String {
+		let items = match self.sources() {
+			[single] => single.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+			sources => format!("{} items", sources.len()),
+		};
+		let dest_name = |dest: &Path| dest.file_name().map_or_else(
+			|| dest.display().to_string(),
+			|name| name.to_string_lossy().into_owned(),
+		);
+		match self {
+			Job::Copy { dest, .. } => format!("Copying {} to {}", items, dest_name(dest)),
+			Job::Move { dest, .. } => format!("Moving {} to {}", items, dest_name(dest)),
+			Job::Delete { .. } => format!("Deleting {}", items),
+		}
+	}
+}
+
+/// Pauses, resumes or cancels a running job from another thread
+#[derive(Clone, Debug, Default)]
+pub struct JobControl {
+	cancel: CancelToken,
+	paused: Arc<(Mutex<bool>, Condvar)>,
+	report_interval: Option<Duration>,
+}
+
+impl JobControl {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Reports progress this often instead of ten times a second, with `Duration::ZERO`
+	/// after every file and chunk
+	pub fn with_report_interval(mut self, interval: Duration) -> Self {
+		self.report_interval = Some(interval);
+		self
+	}
+
+	pub fn pause(&self) {
+		*self.paused.0.lock().unwrap() = true;
+	}
+
+	pub fn resume(&self) {
+		*self.paused.0.lock().unwrap() = false;
+		self.paused.1.notify_all();
+	}
+
+	/// Stops the job at the next file or chunk, a paused job stops right away
+	pub fn cancel(&self) {
+		self.cancel.cancel();
+		self.paused.1.notify_all();
+	}
+
+	pub fn is_paused(&self) -> bool {
+		*self.paused.0.lock().unwrap()
+	}
+
+	pub fn is_cancelled(&self) -> bool {
+		self.cancel.is_cancelled()
+	}
+
+	/// Blocks while the job is paused, returns false once it is cancelled
+	fn wait(&self) -> bool {
+		let mut paused = self.paused.0.lock().unwrap();
+		while *paused && !self.is_cancelled() {
+			paused = self.paused.1.wait(paused).unwrap();
+		}
+		!self.is_cancelled()
+	}
+}
+
+/// How far a job got
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JobProgress {
+	pub bytes_done: u64,
+	pub bytes_total: u64,
+	pub files_done: u64,
+	pub files_total: u64,
+	/// Entry being worked on right now
+	pub current: Option<PathBuf>,
+	/// Time spent working, pauses not included
+	pub elapsed: Duration,
+}
+
+impl JobProgress {
+	/// Part that is done, between 0 and 1. Goes by bytes when the job has any
+	pub fn fraction(&self) -> f32 {
+		let (done, total) = if self.bytes_total > 0 {
+			(self.bytes_done, self.bytes_total)
+		} else {
+			(self.files_done, self.files_total)
+		};
+		if total == 0 { 0.0 } else { (done as f64 / total as f64).min(1.0) as f32 }
+	}
+
+	pub fn bytes_per_second(&self) -> Option<f64> {
+		let seconds = self.elapsed.as_secs_f64();
+		(seconds > 0.0 && self.bytes_done > 0).then(|| self.bytes_done as f64 / seconds)
+	}
+
+	pub fn files_per_second(&self) -> Option<f64> {
+		let seconds = self.elapsed.as_secs_f64();
+		(seconds > 0.0 && self.files_done > 0).then(|| self.files_done as f64 / seconds)
+	}
+
+	/// Time left at the speed so far
+	pub fn eta(&self) -> Option<Duration> {
+		let fraction = self.fraction() as f64;
+		if fraction <= 0.0 || fraction >= 1.0 {
+			return None;
+		}
+		Some(self.elapsed.mul_f64((1.0 - fraction) / fraction))
+	}
+}
+
+/// An entry the job could not handle, the job carried on with the rest
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JobFailure {
+	pub path: PathBuf,
+	pub message: String,
+}
+
+/// How a job ended
+#[derive(Clone, Debug, Default)]
+pub struct JobOutcome {
+	pub progress: JobProgress,
+	pub failures: Vec<JobFailure>,
+	pub cancelled: bool,
+}
+
+/// Runs `job` on the calling thread. Entries that fail are collected and skipped,
+/// `on_progress` is called every now and then and once more right before it returns.
+pub fn run_job(job: &Job, control: &JobControl, on_progress: impl FnMut(&JobProgress)) -> JobOutcome {
+	run(job, None, control, on_progress)
+}
+
+/// Runs the job of `journal` like `run_job`, writing down what it gets done. A job
+/// that ran before goes on where it stopped. The journal is deleted once the job
+/// ended, only a cancelled job keeps it to be resumed later.
+pub fn run_job_journaled(mut journal: CopyJournal, control: &JobControl, on_progress: impl FnMut(&JobProgress)) -> JobOutcome {
+	let job = journal.job().clone();
+	let outcome = run(&job, Some(&mut journal), control, on_progress);
+	if outcome.cancelled {
+		journal.checkpoint();
+	} else {
+		let _ = journal.remove();
+	}
+	outcome
+}
+
+fn run(job: &Job, journal: Option<&mut CopyJournal>, control: &JobControl, on_progress: impl FnMut(&JobProgress)) -> JobOutcome {
+	let options = match job {
+		Job::Copy { options, .. } | Job::Move { options, .. } => *options,
+		Job::Delete { .. } => CopyOptions::default(),
+	};
+	let mut runner = Runner::new(control, options, journal, on_progress);
+
+	match job {
+		Job::Copy { sources, dest, conflicts, .. } => {
+			let transfers = runner.transfers(sources, dest, conflicts);
+			let sources: Vec<PathBuf> = transfers.iter().map(|(_, transfer)| transfer.source.clone()).collect();
+			runner.count(&sources, true);
+			for (index, transfer) in &transfers {
+				match transfer.mode {
+					TransferMode::Replace => runner.replace(&transfer.target, |runner| runner.copy(&transfer.source, &transfer.target)),
+					// Copying into an existing folder merges already
+					TransferMode::Create | TransferMode::Merge => runner.copy(&transfer.source, &transfer.target),
+				}
+				runner.finish(*index);
+			}
+		}
+		Job::Move { sources, dest, conflicts, .. } => {
+			let transfers = runner.transfers(sources, dest, conflicts);
+			runner.progress.files_total = transfers.len() as u64;
+			for (index, transfer) in &transfers {
+				runner.move_to(transfer);
+				runner.finish(*index);
+			}
+		}
+		Job::Delete { paths } => {
+			runner.count(paths, false);
+			for path in paths {
+				runner.delete(path);
+			}
+		}
+	}
+
+	runner.progress.current = None;
+	runner.report(true);
+	JobOutcome {
+		cancelled: control.is_cancelled(),
+		progress: runner.progress,
+		failures: runner.failures,
+	}
+}
+
+/// Copies a file, link or whole folder to `target` on the calling thread, like a copy
+/// job does but without conflict handling. Returns what could not be copied.
+pub fn copy_entry(source: &Path, target: &Path, options: &CopyOptions) -> Vec<JobFailure> {
+	let control = JobControl::new();
+	let mut runner = Runner::new(&control, *options, None, |_: &JobProgress| {});
+	runner.copy(source, target);
+	runner.failures
+}
+
+/// Where one source of a copy or move goes
+#[derive(Clone)]
+pub(crate) struct Transfer {
+	pub(crate) source: PathBuf,
+	pub(crate) target: PathBuf,
+	pub(crate) mode: TransferMode,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TransferMode {
+	/// Nothing is in the way
+	Create,
+	/// The existing target is swapped for the source
+	Replace,
+	/// The source folder is combined with the existing target folder
+	Merge,
+}
+
+struct Runner<'a, F> {
+	control: &'a JobControl,
+	options: CopyOptions,
+	journal: Option<&'a mut CopyJournal>,
+	on_progress: F,
+	progress: JobProgress,
+	failures: Vec<JobFailure>,
+	started: Instant,
+	paused_for: Duration,
+	last_report: Instant,
+}
+
+impl<'a, F: FnMut(&JobProgress)> Runner<'a, F> {
+	fn new(control: &'a JobControl, options: CopyOptions, journal: Option<&'a mut CopyJournal>, on_progress: F) -> Self {
+		Self {
+			control,
+			options,
+			journal,
+			on_progress,
+			progress: JobProgress::default(),
+			failures: Vec::new(),
+			started: Instant::now(),
+			paused_for: Duration::ZERO,
+			last_report: Instant::now(),
+		}
+	}
+
+	/// Waits out a pause, false once the job is cancelled
+	fn proceed(&mut self) -> bool {
+		let waited = Instant::now();
+		let go_on = self.control.wait();
+		self.paused_for += waited.elapsed();
+		self.report(false);
+		go_on
+	}
+
+	fn report(&mut self, force: bool) {
+		if force || self.last_report.elapsed() >= self.control.report_interval.unwrap_or(REPORT_INTERVAL) {
+			self.progress.elapsed = self.started.elapsed().saturating_sub(self.paused_for);
+			self.last_report = Instant::now();
+			(self.on_progress)(&self.progress);
+		}
+	}
+
+	fn fail(&mut self, path: &Path, error: impl ToString) {
+		self.failures.push(JobFailure { path: path.to_path_buf(), message: error.to_string() });
+	}
+
+	/// Adds up what is below `paths` for the totals. A copy counts files and their bytes,
+	/// for a delete every folder is a step of its own too.
+	fn count(&mut self, paths: &[PathBuf], copying: bool) {
+		let follow_links = copying && !self.options.symlinks;
+		for path in paths {
+			for entry in WalkDir::new(path).follow_links(follow_links).follow_root_links(follow_links).into_iter().flatten() {
+				if self.control.is_cancelled() {
+					return;
+				}
+				if copying {
+					if entry.file_type().is_dir() {
+						continue;
+					}
+					if entry.file_type().is_file() {
+						self.progress.bytes_total += entry.metadata().map_or(0, |metadata| metadata.len());
+					}
+				}
+				self.progress.files_total += 1;
+			}
+		}
+	}
+
+	/// The transfers still to do with their place in the plan. A journaled job keeps the
+	/// plan it made the first time, so names taken by its own copies are no conflicts.
+	fn transfers(&mut self, sources: &[PathBuf], dest: &Path, conflicts: &ConflictPolicy) -> Vec<(usize, Transfer)> {
+		let transfers = match self.journal.as_deref().and_then(CopyJournal::plan) {
+			Some(plan) => plan.to_vec(),
+			None => {
+				let transfers = self.plan(sources, dest, conflicts);
+				if let Some(journal) = &mut self.journal {
+					journal.set_plan(&transfers);
+				}
+				transfers
+			}
+		};
+		transfers
+			.into_iter()
+			.enumerate()
+			.filter(|(index, _)| !self.journal.as_ref().is_some_and(|journal| journal.is_finished(*index)))
+			.collect()
+	}
+
+	/// Notes down that the transfer at `index` is through, unless the job was cancelled
+	fn finish(&mut self, index: usize) {
+		if !self.control.is_cancelled() && let Some(journal) = &mut self.journal {
+			journal.finish(index);
+		}
+	}
+
+	/// Works out the target of every source with the decisions for taken names,
+	/// skipped sources and those without a decision are left out
+	fn plan(&mut self, sources: &[PathBuf], dest: &Path, conflicts: &ConflictPolicy) -> Vec<Transfer> {
+		let mut transfers = Vec::new();
+		for source in sources {
+			if let Err(e) = check_destination(source, dest) {
+				self.fail(source, e);
+				continue;
+			}
+			let target = target_path(source, dest);
+			let Ok(existing) = fs::symlink_metadata(&target) else {
+				transfers.push(Transfer { source: source.clone(), target, mode: TransferMode::Create });
+				continue;
+			};
+
+			let action = conflicts.action(source);
+			let mode = match action {
+				Some(ConflictAction::Skip) => continue,
+				Some(ConflictAction::KeepBoth) => {
+					let target = unique_name(&target);
+					transfers.push(Transfer { source: source.clone(), target, mode: TransferMode::Create });
+					continue;
+				}
+				_ if *source == target => {
+					self.fail(source, "Source and destination are the same");
+					continue;
+				}
+				None => {
+					self.fail(source, format!("{} already exists", target.display()));
+					continue;
+				}
+				Some(ConflictAction::Merge) if existing.is_dir() && source.is_dir() => TransferMode::Merge,
+				Some(ConflictAction::Replace | ConflictAction::Merge) => TransferMode::Replace,
+			};
+			transfers.push(Transfer { source: source.clone(), target, mode });
+		}
+		transfers
+	}
+
+	/// Moves the existing `target` aside while `transfer` puts the new entry in its place.
+	/// It is only deleted once that worked, otherwise it is put back. A journaled job that
+	/// is cancelled leaves both where they are, once resumed it goes on with the backup
+	/// the journal noted down.
+	fn replace(&mut self, target: &Path, transfer: impl FnOnce(&mut Self)) {
+		let resumed = self.journal.as_ref()
+			.and_then(|journal| journal.backup(target))
+			.filter(|backup| fs::symlink_metadata(backup).is_ok())
+			.map(Path::to_path_buf);
+		let backup = match resumed {
+			Some(backup) => backup,
+			None => {
+				let name = target.file_name().unwrap_or_default().to_string_lossy();
+				let backup = unique_name(&target.with_file_name(format!(".{}.replaced", name)));
+				if let Err(e) = fs::rename(target, &backup) {
+					self.fail(target, e);
+					return;
+				}
+				if let Some(journal) = &mut self.journal {
+					journal.set_backup(target, &backup);
+				}
+				backup
+			}
+		};
+
+		let failed_before = self.failures.len();
+		transfer(self);
+		if self.failures.len() == failed_before {
+			if !self.control.is_cancelled() {
+				if let Err(e) = remove_entry(&backup) {
+					self.fail(&backup, e);
+				}
+				return;
+			}
+			if self.journal.is_some() {
+				return;
+			}
+		}
+
+		// Whatever made it is dropped again, the old entry is worth more
+		if fs::symlink_metadata(target).is_ok() && let Err(e) = remove_entry(target) {
+			self.fail(target, e);
+			return;
+		}
+		if let Err(e) = fs::rename(&backup, target) {
+			self.fail(&backup, e);
+		}
+	}
+
+	/// Copies `source` with everything below it. Links are recreated as links or followed
+	/// as the options say, pipes, sockets and devices are reported and left out.
+	fn copy(&mut self, source: &Path, target: &Path) {
+		// Folders get their times and mode once everything is in them, deepest first
+		let mut folders = Vec::new();
+		let mut small_files = Vec::new();
+		// A link given as `source` is copied as a link too, not written through
+		let follow_links = !self.options.symlinks;
+		let mut entries = WalkDir::new(source).follow_links(follow_links).follow_root_links(follow_links).into_iter();
+		while let Some(entry) = entries.next() {
+			if !self.proceed() {
+				return;
+			}
+			let entry = match entry {
+				Ok(entry) => entry,
+				Err(e) => {
+					let path = e.path().unwrap_or(source).to_path_buf();
+					self.fail(&path, e);
+					continue;
+				}
+			};
+
+			let to = mirrored(entry.path(), source, target);
+			let file_type = entry.file_type();
+			if file_type.is_dir() {
+				match fs::create_dir_all(&to).and_then(|()| entry.metadata().map_err(io::Error::from)) {
+					Ok(metadata) => folders.push((entry.into_path(), metadata, to)),
+					Err(e) => {
+						self.fail(entry.path(), e);
+						entries.skip_current_dir();
+					}
+				}
+			} else if file_type.is_symlink() {
+				match entry.metadata() {
+					Ok(metadata) if self.resume_point(&metadata, &to) == Some(Resume::Complete) => self.progress.files_done += 1,
+					_ => self.copy_symlink(entry.path(), &to),
+				}
+			} else if let Some(kind) = platform::special_file_kind(&file_type) {
+				self.fail(entry.path(), format!("Skipped, a {} can not be copied", kind));
+				self.progress.files_done += 1;
+			} else {
+				match entry.metadata() {
+					Ok(metadata) if self.resume_point(&metadata, &to) == Some(Resume::Complete) => {
+						self.progress.files_done += 1;
+						self.progress.bytes_done += metadata.len();
+					}
+					Ok(metadata) if self.options.workers > 1 && is_small(&metadata) => {
+						small_files.push(SmallFile { source: entry.into_path(), metadata, target: to });
+						if small_files.len() == BATCH_SIZE {
+							self.copy_small_files(std::mem::take(&mut small_files));
+						}
+					}
+					_ => self.copy_file(entry.path(), &to),
+				}
+			}
+		}
+		self.copy_small_files(small_files);
+		if self.control.is_cancelled() {
+			return;
+		}
+
+		for (folder, metadata, to) in folders.iter().rev() {
+			if let Err(e) = apply_metadata(folder, metadata, to, &self.options) {
+				self.fail(folder, e);
+			}
+		}
+	}
+
+	/// How far an earlier run of a journaled job got with `target`
+	fn resume_point(&self, metadata: &Metadata, target: &Path) -> Option<Resume> {
+		self.journal.as_ref().and_then(|journal| journal.resume(metadata, target))
+	}
+
+	/// Copies one file chunk by chunk, a file that is not complete is removed again.
+	/// A journaled job writes it under its `partial_path` until it is complete and keeps
+	/// what is there when it is cancelled, noting down how much of it is on disk every
+	/// now and then to go on from there later.
+	fn copy_file(&mut self, source: &Path, target: &Path) {
+		self.progress.current = Some(source.to_path_buf());
+		let options = self.options;
+		let written = match self.journal {
+			Some(_) => partial_path(target),
+			None => target.to_path_buf(),
+		};
+		let copied = fs::metadata(source).and_then(|metadata| {
+			let start = match self.resume_point(&metadata, target) {
+				Some(Resume::From(copied)) => copied,
+				_ => 0,
+			};
+			self.progress.bytes_done += start;
+			let (mut position, mut unsynced) = (start, 0);
+			let mut complete = copy_file_contents(source, &metadata, &written, &options, options.verify, start, |writer, bytes| {
+				self.progress.bytes_done += bytes;
+				position += bytes;
+				unsynced += bytes;
+				let go_on = self.proceed();
+				if (!go_on || unsynced >= SYNC_INTERVAL) && let Some(journal) = &mut self.journal && writer.sync_data().is_ok() {
+					journal.note_partial(&metadata, target, position);
+					unsynced = 0;
+				}
+				go_on
+			})?;
+			if complete && options.verify {
+				complete = verify_contents(source, &written, || self.proceed())?;
+			}
+			if complete {
+				apply_metadata(source, &metadata, &written, &options)?;
+				if let Some(journal) = &mut self.journal {
+					fs::rename(&written, target)?;
+					journal.note_complete(&metadata, target);
+				}
+			}
+			Ok(complete)
+		});
+		let keep_partial = self.journal.is_some();
+		self.file_copied(source, &written, copied, keep_partial);
+	}
+
+	/// Counts a copied file, a file that is not complete is removed again unless
+	/// `keep_partial` says a resumed job goes on with it
+	fn file_copied(&mut self, source: &Path, written: &Path, copied: io::Result<bool>, keep_partial: bool) {
+		match copied {
+			Ok(true) => self.progress.files_done += 1,
+			Ok(false) => {
+				if !keep_partial {
+					let _ = fs::remove_file(written);
+				}
+			}
+			Err(e) => {
+				let _ = fs::remove_file(written);
+				self.fail(source, e);
+				self.progress.files_done += 1;
+			}
+		}
+	}
+
+	/// Copies files that fit in one chunk on a few threads at once, the time for opening
+	/// and closing them adds up more than their contents do. Progress, pauses and failures
+	/// are dealt with here as every file comes back.
+	fn copy_small_files(&mut self, files: Vec<SmallFile>) {
+		if files.is_empty() {
+			return;
+		}
+		let (control, options) = (self.control, self.options);
+		// Written under a name of their own like larger files, a crash can cut them short as well
+		let journaled = self.journal.is_some();
+		let next = AtomicUsize::new(0);
+		let (sender, receiver) = mpsc::channel();
+		thread::scope(|scope| {
+			for _ in 0..options.workers.min(files.len()) {
+				let sender = sender.clone();
+				let (files, next) = (&files, &next);
+				scope.spawn(move || {
+					while control.wait() {
+						let Some(file) = files.get(next.fetch_add(1, Ordering::Relaxed)) else { break };
+						let written = if journaled { partial_path(&file.target) } else { file.target.clone() };
+						let copied = copy_file_contents(&file.source, &file.metadata, &written, &options, options.verify, 0, |_, _| true)
+							.and_then(|complete| if complete && options.verify {
+								verify_contents(&file.source, &written, || control.wait())
+							} else {
+								Ok(complete)
+							})
+							.and_then(|complete| {
+								if complete {
+									apply_metadata(&file.source, &file.metadata, &written, &options)?;
+									if journaled {
+										fs::rename(&written, &file.target)?;
+									}
+								}
+								Ok(complete)
+							});
+						if sender.send((file, written, copied)).is_err() {
+							break;
+						}
+					}
+				});
+			}
+			drop(sender);
+
+			for (file, written, copied) in receiver {
+				self.progress.current = Some(file.source.clone());
+				if matches!(copied, Ok(true)) {
+					self.progress.bytes_done += file.metadata.len();
+					if let Some(journal) = &mut self.journal {
+						journal.note_complete(&file.metadata, &file.target);
+					}
+				}
+				self.file_copied(&file.source, &written, copied, false);
+				// Keeps the time spent paused out of the elapsed time, the workers wait too
+				self.proceed();
+			}
+		});
+	}
+
+	fn copy_symlink(&mut self, source: &Path, target: &Path) {
+		self.progress.current = Some(source.to_path_buf());
+		let copied = platform::copy_symlink(source, target)
+			.and_then(|()| fs::symlink_metadata(source))
+			.and_then(|metadata| apply_metadata(source, &metadata, target, &self.options));
+		if let Err(e) = copied {
+			self.fail(source, e);
+		}
+		self.progress.files_done += 1;
+	}
+
+	fn move_to(&mut self, transfer: &Transfer) {
+		if !self.proceed() {
+			return;
+		}
+		let (source, target) = (&transfer.source, &transfer.target);
+		self.progress.current = Some(source.clone());
+		match transfer.mode {
+			TransferMode::Create => self.rename(source, target),
+			TransferMode::Replace => self.replace(target, |runner| runner.rename(source, target)),
+			TransferMode::Merge => self.merge_move(source, target),
+		}
+		self.progress.files_done += 1;
+	}
+
+	/// Renames, or copies and deletes when `target` is on another file system
+	fn rename(&mut self, source: &Path, target: &Path) {
+		match fs::rename(source, target) {
+			Ok(()) => {}
+			Err(e) if e.kind() == io::ErrorKind::CrossesDevices => self.move_across(source, target),
+			Err(e) => self.fail(source, e),
+		}
+	}
+
+	/// Copies `source` to `target`, checks the copy and only then deletes the source.
+	/// If anything goes wrong before that the copy is removed again and the source
+	/// stays as it was.
+	fn move_across(&mut self, source: &Path, target: &Path) {
+		// The copy shows progress like any other, on top of the one step for the move
+		self.count(&[source.to_path_buf()], true);
+		let failed_before = self.failures.len();
+		self.copy(source, target);
+		if self.failures.len() == failed_before
+			&& !self.control.is_cancelled()
+			&& let Err(e) = verify_copy(source, target, !self.options.symlinks) {
+			self.fail(source, e);
+		}
+
+		if self.failures.len() > failed_before || self.control.is_cancelled() {
+			// A journaled job goes on with what it copied once it is resumed
+			let resumable = self.journal.is_some() && self.failures.len() == failed_before;
+			if !resumable && fs::symlink_metadata(target).is_ok() && let Err(e) = remove_entry(target) {
+				self.fail(target, e);
+			}
+			return;
+		}
+		if let Err(e) = remove_entry(source) {
+			self.fail(source, e);
+		}
+	}
+
+	/// Moves what is in the folder `source` into the existing folder `target`,
+	/// folders in both are merged as well and other entries in both are replaced
+	fn merge_move(&mut self, source: &Path, target: &Path) {
+		let failed_before = self.failures.len();
+		let entries = match fs::read_dir(source) {
+			Ok(entries) => entries,
+			Err(e) => return self.fail(source, e),
+		};
+		for entry in entries {
+			let entry = match entry {
+				Ok(entry) => entry,
+				Err(e) => {
+					self.fail(source, e);
+					continue;
+				}
+			};
+
+			let (from, to) = (entry.path(), target.join(entry.file_name()));
+			match fs::symlink_metadata(&to) {
+				Err(_) => self.rename(&from, &to),
+				Ok(existing) if existing.is_dir() && entry.file_type().is_ok_and(|kind| kind.is_dir()) => {
+					self.merge_move(&from, &to);
+				}
+				Ok(_) => self.replace(&to, |runner| runner.rename(&from, &to)),
+			}
+		}
+
+		// Only empty once everything made it
+		if self.failures.len() == failed_before && let Err(e) = fs::remove_dir(source) {
+			self.fail(source, e);
+		}
+	}
+
+	/// Deletes the contents first, what can not be removed that way gets
+	/// another try through `delete_path`, which elevates if it has to
+	fn delete(&mut self, path: &Path) {
+		let failed_before = self.failures.len();
+		// A link to a folder is removed as a link, what it points to stays
+		for entry in WalkDir::new(path).follow_links(false).follow_root_links(false).contents_first(true) {
+			if !self.proceed() {
+				return;
+			}
+			let entry = match entry {
+				Ok(entry) => entry,
+				Err(e) => {
+					let failed = e.path().unwrap_or(path).to_path_buf();
+					self.fail(&failed, e);
+					continue;
+				}
+			};
+
+			self.progress.current = Some(entry.path().to_path_buf());
+			let removed = if entry.file_type().is_dir() {
+				fs::remove_dir(entry.path())
+			} else {
+				fs::remove_file(entry.path())
+			};
+			if let Err(e) = removed {
+				self.fail(entry.path(), e);
+			}
+			self.progress.files_done += 1;
+		}
+
+		if self.failures.len() > failed_before
+			&& fs::symlink_metadata(path).is_ok()
+			&& delete_path(path).is_ok() {
+			self.failures.truncate(failed_before);
+		}
+	}
+}
+
+/// A file for the worker threads, together with what is known about it from the walk
+struct SmallFile {
+	source: PathBuf,
+	metadata: Metadata,
+	target: PathBuf,
+}
+
+/// Small enough to be copied in one go, sparse files need the hole handling of a big one
+fn is_small(metadata: &Metadata) -> bool {
+	metadata.is_file() && metadata.len() <= CHUNK_SIZE as u64 && !platform::is_sparse(metadata)
+}
+
+/// Creates `target` and fills it from `source` with `copy_contents`, or goes on from
+/// `start` with the `target` that is there. A `durable` copy is written out to the disk
+/// before this returns, so checking it reads what is really there.
+fn copy_file_contents(
+	source: &Path,
+	metadata: &Metadata,
+	target: &Path,
+	options: &CopyOptions,
+	durable: bool,
+	start: u64,
+	on_chunk: impl FnMut(&File, u64) -> bool,
+) -> io::Result<bool> {
+	let mut reader = File::open(source)?;
+	let mut writer = if start > 0 {
+		let mut writer = OpenOptions::new().write(true).open(target)?;
+		// Whatever came after the point that was noted down may not be on disk
+		writer.set_len(start)?;
+		writer.seek(SeekFrom::Start(start))?;
+		reader.seek(SeekFrom::Start(start))?;
+		writer
+	} else {
+		File::create(target)?
+	};
+	let sparse = options.sparse && platform::is_sparse(metadata);
+	let complete = copy_contents(&mut reader, &mut writer, options, sparse, on_chunk)?;
+	if complete && durable {
+		writer.sync_all()?;
+	}
+	Ok(complete)
+}
+
+/// Where `path` below `source` ends up when `source` is copied to `target`
+fn mirrored(path: &Path, source: &Path, target: &Path) -> PathBuf {
+	match path.strip_prefix(source) {
+		Ok(relative) if !relative.as_os_str().is_empty() => target.join(relative),
+		_ => target.to_path_buf(),
+	}
+}
+
+/// Removes a file, link or whole folder
+pub(crate) fn remove_entry(path: &Path) -> io::Result<()> {
+	if fs::symlink_metadata(path)?.is_dir() {
+		fs::remove_dir_all(path)
+	} else {
+		fs::remove_file(path)
+	}
+}
+
+/// Checks that everything below `source` made it to `target` with the same size,
+/// links that were kept have to point to the same place
+fn verify_copy(source: &Path, target: &Path, follow_links: bool) -> io::Result<()> {
+	for entry in WalkDir::new(source).follow_links(follow_links).follow_root_links(follow_links) {
+		let entry = entry?;
+		let copy = mirrored(entry.path(), source, target);
+
+		let (original, copied) = (entry.metadata()?, fs::symlink_metadata(&copy)?);
+		let matches = if original.is_symlink() {
+			copied.is_symlink() && fs::read_link(entry.path())? == fs::read_link(&copy)?
+		} else {
+			original.is_dir() == copied.is_dir() && (original.is_dir() || original.len() == copied.len())
+		};
+		if !matches {
+			return Err(io::Error::other(format!("{} does not match the original", copy.display())));
+		}
+	}
+	Ok(())
+}
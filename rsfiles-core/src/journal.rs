@@ -0,0 +1,422 @@
+use crate::job::{remove_entry, Transfer, TransferMode};
+use crate::{platform, ConflictPolicy, CopyOptions, Job};
+use std::{
+	collections::{HashMap, HashSet},
+	ffi::OsString,
+	fs::{self, File, Metadata, OpenOptions},
+	io::{self, Write},
+	path::{Path, PathBuf},
+	time::{Duration, Instant, UNIX_EPOCH},
+};
+use walkdir::WalkDir;
+
+// First line of every journal, the number goes up when the format changes
+const HEADER: &str = "rsfiles journal 1";
+// A file that is copied partly is written out to disk and noted down after this many bytes
+pub(crate) const SYNC_INTERVAL: u64 = 64 * 1024 * 1024;
+// Lines wait in memory until this many came together or the oldest waited this long,
+// then they go to disk in one go with the copies they are about
+const CHECKPOINT_LINES: usize = 1024;
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What a copy or move job got done so far, kept in a small file while it runs so
+/// the job can go on after it was cancelled or rsfiles went down. Files it finished
+/// are skipped as long as the original has the same size and modification time,
+/// a file it copied partly goes on from the last point that was written out to disk.
+///
+/// The journal only ever grows by whole lines, what a crash cut off halfway is ignored.
+/// Most lines are written out every now and then instead of one by one, a crash loses
+/// the last few and that work is done again.
+pub struct CopyJournal {
+	path: PathBuf,
+	job: Job,
+	/// Where every source goes, fixed the first time the job runs
+	plan: Option<Vec<Transfer>>,
+	finished: HashSet<usize>,
+	/// Entries a replace moved aside, by the target they were moved away from
+	backups: HashMap<PathBuf, PathBuf>,
+	files: HashMap<PathBuf, FileRecord>,
+	file: File,
+	/// Lines not written yet, and the copies they say are complete
+	pending: String,
+	pending_lines: usize,
+	unsynced: Vec<PathBuf>,
+	last_checkpoint: Instant,
+}
+
+/// A target file and the size and modification time of the original it is a copy of
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct FileRecord {
+	size: u64,
+	modified: Duration,
+	/// Bytes that are on disk, `None` once the copy is complete
+	copied: Option<u64>,
+}
+
+/// How far a file got in an earlier run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Resume {
+	Complete,
+	From(u64),
+}
+
+impl CopyJournal {
+	/// Starts a journal for `job` at `path`, only copies and moves can have one
+	pub fn create(path: impl Into<PathBuf>, job: &Job) -> io::Result<Self> {
+		let path = path.into();
+		let (kind, sources, dest, options) = match job {
+			Job::Copy { sources, dest, options, .. } => ("copy", sources, dest, options),
+			Job::Move { sources, dest, options, .. } => ("move", sources, dest, options),
+			Job::Delete { .. } => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Only copies and moves have a journal")),
+		};
+		let mut lines = vec![line(&[HEADER]), line(&["job", kind]), line(&["dest", &encode(dest)?])];
+		for source in sources {
+			lines.push(line(&["source", &encode(source)?]));
+		}
+		lines.push(line(&["options", &encode_options(options)]));
+
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent)?;
+		}
+		let mut file = File::create(&path)?;
+		file.write_all(lines.concat().as_bytes())?;
+		file.sync_data()?;
+		Ok(Self {
+			path,
+			job: job.clone(),
+			plan: None,
+			finished: HashSet::new(),
+			backups: HashMap::new(),
+			files: HashMap::new(),
+			file,
+			pending: String::new(),
+			pending_lines: 0,
+			unsynced: Vec::new(),
+			last_checkpoint: Instant::now(),
+		})
+	}
+
+	/// Reads the journal a job left at `path`
+	pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+		let path = path.into();
+		let contents = fs::read_to_string(&path)?;
+		let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a job journal", path.display()));
+		// A last line without its line break is one a crash cut off
+		let complete = contents.rfind('\n').map_or("", |end| &contents[..=end]);
+		let mut lines = complete.split_terminator('\n');
+		if lines.next() != Some(HEADER) {
+			return Err(invalid());
+		}
+
+		let (mut kind, mut dest, mut sources, mut options) = (None, None, Vec::new(), CopyOptions::default());
+		let (mut planned, mut plan) = (Vec::new(), None);
+		let (mut finished, mut backups, mut files) = (HashSet::new(), HashMap::new(), HashMap::new());
+		for line in lines {
+			let fields: Vec<String> = line.split('\t').map(decode).collect();
+			let fields: Vec<&str> = fields.iter().map(String::as_str).collect();
+			match fields[..] {
+				["job", job] => kind = Some(job == "move"),
+				["dest", path] => dest = Some(PathBuf::from(path)),
+				["source", path] => sources.push(PathBuf::from(path)),
+				["options", encoded] => options = decode_options(encoded).unwrap_or(options),
+				["transfer", mode, source, target] => {
+					let mode = match mode {
+						"create" => TransferMode::Create,
+						"replace" => TransferMode::Replace,
+						"merge" => TransferMode::Merge,
+						_ => continue,
+					};
+					planned.push(Transfer { source: source.into(), target: target.into(), mode });
+				}
+				// The plan only counts once all of it made it into the journal
+				["planned", count] if count.parse() == Ok(planned.len()) => plan = Some(std::mem::take(&mut planned)),
+				["finished", index] => finished.extend(index.parse::<usize>()),
+				["backup", target, backup] => {
+					backups.insert(PathBuf::from(target), PathBuf::from(backup));
+				}
+				["done", size, secs, nanos, target] => {
+					if let Some(record) = parse_record(size, secs, nanos) {
+						files.insert(PathBuf::from(target), record);
+					}
+				}
+				["part", size, secs, nanos, copied, target] => {
+					if let (Some(record), Ok(copied)) = (parse_record(size, secs, nanos), copied.parse()) {
+						files.insert(PathBuf::from(target), FileRecord { copied: Some(copied), ..record });
+					}
+				}
+				_ => {}
+			}
+		}
+
+		let (Some(is_move), Some(dest)) = (kind, dest) else { return Err(invalid()) };
+		let conflicts = ConflictPolicy::new();
+		let job = if is_move {
+			Job::Move { sources, dest, conflicts, options }
+		} else {
+			Job::Copy { sources, dest, conflicts, options }
+		};
+		let file = OpenOptions::new().append(true).open(&path)?;
+		Ok(Self {
+			path,
+			job,
+			plan,
+			finished,
+			backups,
+			files,
+			file,
+			pending: String::new(),
+			pending_lines: 0,
+			unsynced: Vec::new(),
+			last_checkpoint: Instant::now(),
+		})
+	}
+
+	/// Every journal in `dir`, oldest first. Those that can not be read are left out.
+	pub fn load_all(dir: &Path) -> Vec<Self> {
+		let Ok(entries) = fs::read_dir(dir) else { return Vec::new() };
+		let mut paths: Vec<PathBuf> = entries
+			.flatten()
+			.map(|entry| entry.path())
+			.filter(|path| path.extension().is_some_and(|extension| extension == "journal"))
+			.collect();
+		paths.sort();
+		paths.into_iter().filter_map(|path| Self::load(path).ok()).collect()
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// The job as it was started, decisions about taken names are part of the plan
+	pub fn job(&self) -> &Job {
+		&self.job
+	}
+
+	/// Deletes the journal, the job can not be resumed anymore
+	pub fn remove(self) -> io::Result<()> {
+		fs::remove_file(&self.path)
+	}
+
+	/// Deletes the journal along with the files the job only copied partly, for a job
+	/// that is not going to be resumed. Entries it was replacing are put back in place of
+	/// what it got done of their replacement. Finished copies stay where they are.
+	pub fn discard(self) -> io::Result<()> {
+		// Files cut short by a crash were never noted down, they are found by their names
+		for transfer in self.plan.iter().flatten() {
+			for entry in WalkDir::new(&transfer.target).follow_links(false) {
+				let Ok(entry) = entry else { continue };
+				if entry.file_type().is_file() && is_partial(entry.path()) {
+					fs::remove_file(entry.path())?;
+				}
+			}
+		}
+		// A backup that is still there belongs to a replace that never got through
+		for (target, backup) in &self.backups {
+			if fs::symlink_metadata(backup).is_err() {
+				continue;
+			}
+			if fs::symlink_metadata(target).is_ok() {
+				remove_entry(target)?;
+			}
+			fs::rename(backup, target)?;
+		}
+		self.remove()
+	}
+
+	pub(crate) fn plan(&self) -> Option<&[Transfer]> {
+		self.plan.as_deref()
+	}
+
+	pub(crate) fn set_plan(&mut self, transfers: &[Transfer]) {
+		let mut lines = String::new();
+		for transfer in transfers {
+			let (Ok(source), Ok(target)) = (encode(&transfer.source), encode(&transfer.target)) else { return };
+			let mode = match transfer.mode {
+				TransferMode::Create => "create",
+				TransferMode::Replace => "replace",
+				TransferMode::Merge => "merge",
+			};
+			lines += &line(&["transfer", mode, &source, &target]);
+		}
+		lines += &line(&["planned", &transfers.len().to_string()]);
+		// Nothing is copied before the plan is on disk, a new plan could pick other names
+		self.append_now(&lines);
+		self.plan = Some(transfers.to_vec());
+	}
+
+	pub(crate) fn is_finished(&self, transfer: usize) -> bool {
+		self.finished.contains(&transfer)
+	}
+
+	pub(crate) fn finish(&mut self, transfer: usize) {
+		self.append(&line(&["finished", &transfer.to_string()]));
+		self.finished.insert(transfer);
+	}
+
+	/// Where a replace moved the entry at `target` that was there before
+	pub(crate) fn backup(&self, target: &Path) -> Option<&Path> {
+		self.backups.get(target).map(PathBuf::as_path)
+	}
+
+	pub(crate) fn set_backup(&mut self, target: &Path, backup: &Path) {
+		if let (Ok(encoded_target), Ok(encoded_backup)) = (encode(target), encode(backup)) {
+			// The entry is moved aside already, a restart must know where it went
+			self.append_now(&line(&["backup", &encoded_target, &encoded_backup]));
+			self.backups.insert(target.to_path_buf(), backup.to_path_buf());
+		}
+	}
+
+	/// How far `target` got as a copy of the original with `metadata`, `None` when it
+	/// has to be copied from the start
+	pub(crate) fn resume(&self, metadata: &Metadata, target: &Path) -> Option<Resume> {
+		let record = self.files.get(target)?;
+		if Some((record.size, record.modified)) != stamp(metadata) {
+			return None;
+		}
+		match record.copied {
+			None => {
+				let existing = fs::symlink_metadata(target).ok()?;
+				(!metadata.is_file() || existing.len() == record.size).then_some(Resume::Complete)
+			}
+			Some(copied) => {
+				let existing = fs::symlink_metadata(partial_path(target)).ok()?;
+				(existing.is_file() && existing.len() >= copied).then_some(Resume::From(copied))
+			}
+		}
+	}
+
+	pub(crate) fn note_complete(&mut self, metadata: &Metadata, target: &Path) {
+		self.unsynced.push(target.to_path_buf());
+		self.note(metadata, target, None);
+	}
+
+	/// `copied` bytes of `target` are on disk under its `partial_path`, the caller made sure of that
+	pub(crate) fn note_partial(&mut self, metadata: &Metadata, target: &Path, copied: u64) {
+		self.note(metadata, target, Some(copied));
+	}
+
+	fn note(&mut self, metadata: &Metadata, target: &Path, copied: Option<u64>) {
+		let (Some((size, modified)), Ok(encoded)) = (stamp(metadata), encode(target)) else { return };
+		let (size_text, secs, nanos) = (size.to_string(), modified.as_secs().to_string(), modified.subsec_nanos().to_string());
+		self.append(&match copied {
+			None => line(&["done", &size_text, &secs, &nanos, &encoded]),
+			Some(copied) => line(&["part", &size_text, &secs, &nanos, &copied.to_string(), &encoded]),
+		});
+		self.files.insert(target.to_path_buf(), FileRecord { size, modified, copied });
+	}
+
+	/// Writes the lines that are waiting out to disk, but only once the copies they say
+	/// are complete are on disk themselves. When those can not be written out the lines
+	/// are dropped and the copies made again after a restart. A line that did not make it
+	/// only means more is copied again, so failing to write one does not stop the job.
+	pub(crate) fn checkpoint(&mut self) {
+		self.last_checkpoint = Instant::now();
+		if self.pending.is_empty() {
+			return;
+		}
+		let lines = std::mem::take(&mut self.pending);
+		self.pending_lines = 0;
+		let synced = platform::sync_files(&self.unsynced);
+		self.unsynced.clear();
+		if synced.is_ok() {
+			let _ = self.file.write_all(lines.as_bytes()).and_then(|()| self.file.sync_data());
+		}
+	}
+
+	/// Lines wait for the next checkpoint
+	fn append(&mut self, lines: &str) {
+		self.pending += lines;
+		self.pending_lines += 1;
+		if self.pending_lines >= CHECKPOINT_LINES || self.last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL {
+			self.checkpoint();
+		}
+	}
+
+	/// Lines that are on disk once this returns, together with all that waited before them
+	fn append_now(&mut self, lines: &str) {
+		self.append(lines);
+		self.checkpoint();
+	}
+}
+
+/// Where a journaled job writes `target` until it is complete, so a file that is cut
+/// short never shows up under the name of a finished copy
+pub(crate) fn partial_path(target: &Path) -> PathBuf {
+	let mut name = OsString::from(".");
+	name.push(target.file_name().unwrap_or_default());
+	name.push(".rsfiles-part");
+	target.with_file_name(name)
+}
+
+/// Whether `path` is named like the `partial_path` of a file
+fn is_partial(path: &Path) -> bool {
+	let name = path.file_name().unwrap_or_default().as_encoded_bytes();
+	name.starts_with(b".") && name.ends_with(b".rsfiles-part")
+}
+
+/// Size and modification time that tell whether the original changed since
+fn stamp(metadata: &Metadata) -> Option<(u64, Duration)> {
+	let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+	Some((metadata.len(), modified))
+}
+
+/// A complete file, the fields of a `done` line
+fn parse_record(size: &str, secs: &str, nanos: &str) -> Option<FileRecord> {
+	let modified = Duration::new(secs.parse().ok()?, nanos.parse().ok()?);
+	Some(FileRecord { size: size.parse().ok()?, modified, copied: None })
+}
+
+fn line(fields: &[&str]) -> String {
+	fields.join("\t") + "\n"
+}
+
+/// A path as a field of a line, tabs and line breaks in names are escaped. Paths that
+/// are not valid Unicode can not be written down, their files are copied from the start.
+fn encode(path: &Path) -> io::Result<String> {
+	let Some(path) = path.to_str() else {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} can not be written to a journal", path.display())));
+	};
+	Ok(path.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n").replace('\r', "\\r"))
+}
+
+fn decode(field: &str) -> String {
+	let mut decoded = String::with_capacity(field.len());
+	let mut chars = field.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			decoded.push(c);
+			continue;
+		}
+		match chars.next() {
+			Some('t') => decoded.push('\t'),
+			Some('n') => decoded.push('\n'),
+			Some('r') => decoded.push('\r'),
+			Some(other) => decoded.push(other),
+			None => break,
+		}
+	}
+	decoded
+}
+
+fn encode_options(options: &CopyOptions) -> String {
+	let flags = [
+		options.permissions,
+		options.times,
+		options.symlinks,
+		options.xattrs,
+		options.sparse,
+		options.ownership,
+		options.kernel_copy,
+		options.verify,
+	];
+	let flags: String = flags.iter().map(|&flag| if flag { '1' } else { '0' }).collect();
+	format!("{} {}", flags, options.workers)
+}
+
+fn decode_options(encoded: &str) -> Option<CopyOptions> {
+	let (flags, workers) = encoded.split_once(' ')?;
+	let flags: Vec<bool> = flags.chars().map(|c| c == '1').collect();
+	let [permissions, times, symlinks, xattrs, sparse, ownership, kernel_copy, verify] = flags[..] else { return None };
+	let workers = workers.parse().ok()?;
+	Some(CopyOptions { permissions, times, symlinks, xattrs, sparse, ownership, kernel_copy, workers, verify })
+}
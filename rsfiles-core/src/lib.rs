@@ -0,0 +1,41 @@
+//! File system engine of rsfiles.
+//!
+//! Everything in here works on plain Rust types, no GUI is needed to list,
+//! sort, copy, move or delete files, so other tools can embed it directly.
+
+mod cache;
+mod cancel;
+mod collate;
+mod conflict;
+mod copy;
+mod dir_size;
+mod entry;
+mod error;
+mod format;
+mod job;
+mod journal;
+mod listing;
+mod navigation;
+mod ops;
+pub mod platform;
+mod shortcut;
+mod sort;
+mod watch;
+
+pub use cache::{CacheStats, ListingCache};
+pub use cancel::CancelToken;
+pub use collate::{natural_cmp, Collation};
+pub use conflict::{find_conflicts, target_path, unique_name, Conflict, ConflictAction, ConflictPolicy};
+pub use copy::CopyOptions;
+pub use dir_size::{directory_size, DirSize, SizeCache};
+pub use entry::{FileEntry, FileKind, LinkState, PathExt, SymlinkInfo};
+pub use error::{Error, Result};
+pub use format::{format_size, format_time, format_time_ago, format_time_full, format_time_styled, TimeStyle};
+pub use job::{copy_entry, run_job, run_job_journaled, Job, JobControl, JobFailure, JobOutcome, JobProgress};
+pub use journal::CopyJournal;
+pub use listing::{load_directory_contents, read_file_entry, stream_directory_contents, DirectoryStream};
+pub use navigation::{NavigationState, ViewHistory};
+pub use ops::{check_destination, copy_dir_all, copy_path, delete_path, move_path};
+pub use shortcut::ShortcutInfo;
+pub use sort::{merge_sorted, sort_directory_contents, SortColumn, SortOrder};
+pub use watch::{watch_directory, ChangeBatch, DirectoryWatcher, FsChange};
\ No newline at end of file
@@ -0,0 +1,130 @@
+use crate::{platform, sort_directory_contents, CancelToken, Error, FileEntry, FileKind, LinkState, Result, SortOrder, SymlinkInfo};
+use std::{fs, io, path::{Path, PathBuf}};
+
+/// Loads directory contents with proper hidden file checking.
+/// Only fails when the directory itself can not be read, broken entries
+/// are part of the result and entries without a name are skipped.
+pub fn load_directory_contents(path: &PathBuf) -> Result<Vec<FileEntry>> {
+	let mut files = Vec::new();
+	for batch in stream_directory_contents(path, usize::MAX, CancelToken::new())?.flatten() {
+		files.extend(batch);
+	}
+
+	// Sort the files using the separate sorting function
+	sort_directory_contents(&mut files, &SortOrder::default());
+
+	Ok(files)
+}
+
+// Upper bound for the growing batch size of `DirectoryStream`
+const MAX_BATCH_SIZE: usize = 16 * 1024;
+
+/// Starts reading a directory, the returned iterator yields the entries unsorted
+/// and stops once `cancel` is set. Entries whose metadata can not be read are
+/// still listed (see `FileEntry::error`), only entries the directory itself fails
+/// to return come out as a non fatal `Err`. The first batch holds `batch_size` entries and
+/// every following one doubles, so the first rows show up quickly while merging
+/// the batches into a sorted list stays cheap for huge folders.
+pub fn stream_directory_contents(path: &PathBuf, batch_size: usize, cancel: CancelToken) -> Result<DirectoryStream> {
+	let entries = fs::read_dir(path)
+		.map_err(|e| Error::ReadDir(path.clone(), e))?;
+
+	Ok(DirectoryStream {
+		path: path.clone(),
+		entries,
+		batch_size: batch_size.max(1),
+		cancel,
+		pending_error: None,
+	})
+}
+
+pub struct DirectoryStream {
+	path: PathBuf,
+	entries: fs::ReadDir,
+	batch_size: usize,
+	cancel: CancelToken,
+	pending_error: Option<Error>,
+}
+
+impl Iterator for DirectoryStream {
+	type Item = Result<Vec<FileEntry>>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if let Some(e) = self.pending_error.take() {
+			return Some(Err(e));
+		}
+
+		let mut batch = Vec::new();
+		while batch.len() < self.batch_size && !self.cancel.is_cancelled() {
+			let Some(entry) = self.entries.next() else { break };
+			match entry {
+				Ok(entry) => batch.push(read_entry(&entry)),
+				// Not even the name is known, hand the error out and keep going
+				Err(e) if batch.is_empty() => return Some(Err(Error::ReadDir(self.path.clone(), e))),
+				Err(e) => {
+					self.pending_error = Some(Error::ReadDir(self.path.clone(), e));
+					break;
+				}
+			}
+		}
+
+		self.batch_size = self.batch_size.saturating_mul(2).min(MAX_BATCH_SIZE.max(self.batch_size));
+		if batch.is_empty() { None } else { Some(Ok(batch)) }
+	}
+}
+
+/// Reads a single entry, when its metadata can not be read it is still
+/// returned but marked as unreadable with the reason
+fn read_entry(entry: &fs::DirEntry) -> FileEntry {
+	// Hidden state is only cosmetic, a failure here is not worth an unreadable entry
+	let is_hidden = platform::is_file_hidden(entry).unwrap_or(false);
+	// The file type usually comes from the directory listing itself, no extra stat needed
+	entry_from_path(entry.path(), entry.file_type(), is_hidden)
+}
+
+/// Reads the entry at `path` on its own, like it would show up in a listing of
+/// its parent. `None` when nothing exists at that path (anymore).
+pub fn read_file_entry(path: &Path) -> Option<FileEntry> {
+	let file_type = match fs::symlink_metadata(path) {
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+		metadata => metadata.map(|metadata| metadata.file_type()),
+	};
+	let is_hidden = platform::is_path_hidden(path).unwrap_or(false);
+	Some(entry_from_path(path.to_path_buf(), file_type, is_hidden))
+}
+
+fn entry_from_path(path: PathBuf, file_type: io::Result<fs::FileType>, is_hidden: bool) -> FileEntry {
+	let display_name = path
+		.file_name()
+		.unwrap_or_default()
+		.to_string_lossy()
+		.to_string();
+
+	match read_entry_metadata(file_type.as_ref().ok(), &path) {
+		Ok((metadata, link)) => FileEntry::from_metadata(path, display_name, &metadata, is_hidden, link),
+		Err(e) => {
+			let kind = file_type.map(FileKind::from).unwrap_or(FileKind::Unknown);
+			FileEntry::unreadable(path, display_name, kind, is_hidden, e.to_string())
+		}
+	}
+}
+
+fn read_entry_metadata(file_type: Option<&fs::FileType>, path: &PathBuf) -> io::Result<(fs::Metadata, Option<SymlinkInfo>)> {
+	let is_symlink = match file_type {
+		Some(file_type) => file_type.is_symlink(),
+		None => fs::symlink_metadata(path)?.file_type().is_symlink(),
+	};
+	let link = if is_symlink {
+		Some(SymlinkInfo::read(path)?)
+	} else {
+		None
+	};
+
+	// Links that can be followed show the type, size and date of their target
+	let metadata = match &link {
+		Some(link) if link.state == LinkState::Ok => fs::metadata(path)?,
+		_ => fs::symlink_metadata(path)?,
+	};
+
+	Ok((metadata, link))
+}
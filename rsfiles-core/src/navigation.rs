@@ -1,4 +1,5 @@
-use std::{env, path::PathBuf};
+use crate::SortOrder;
+use std::{collections::HashMap, env, path::PathBuf};
 
 #[derive(Clone)]
 pub struct NavigationState {
@@ -7,6 +8,8 @@ pub struct NavigationState {
     pub history: Vec<ViewHistory>,
     pub history_index: usize,
     pub max_history: usize,
+    /// Sort order chosen per directory, directories not in here use the default
+    pub sort_orders: HashMap<PathBuf, SortOrder>,
 }
 
 #[derive(Clone)]
@@ -20,7 +23,12 @@ impl ViewHistory {
         Self { path, scroll }
     }
 }
-#[allow(dead_code)]
+impl Default for NavigationState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NavigationState {
     pub fn new() -> Self {
         let current_path = env::current_dir()
@@ -33,6 +41,7 @@ impl NavigationState {
             history: vec![ViewHistory::new(current_path, 0.0)],
             history_index: 0,
             max_history: 50,
+            sort_orders: HashMap::new(),
         }
     }
 
@@ -66,10 +75,9 @@ impl NavigationState {
         }
 
         // Don't add duplicate consecutive entries
-        if let Some(last_entry) = self.history.last() {
-            if last_entry.path == path {
-                return;
-            }
+        if let Some(last_entry) = self.history.last()
+            && last_entry.path == path {
+            return;
         }
 
         self.history.push(ViewHistory::new(path, scroll));
@@ -114,6 +122,19 @@ impl NavigationState {
         }
     }
 
+    /// Sort order of the current directory
+    pub fn sort_order(&self) -> SortOrder {
+        self.sort_orders
+            .get(&self.current_path)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Remember the sort order for the current directory
+    pub fn set_sort_order(&mut self, order: SortOrder) {
+        self.sort_orders.insert(self.current_path.clone(), order);
+    }
+
     /// Get the current scroll position
     pub fn get_current_scroll(&self) -> f32 {
         self.history
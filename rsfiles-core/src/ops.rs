@@ -0,0 +1,104 @@
+use crate::{copy_entry, platform, CopyOptions, Error, JobFailure, Result};
+use std::{fs, io, path::{Path, PathBuf}};
+
+/// Copies a folder with everything in it, keeping links, times and modes
+pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
+	first_failure(copy_entry(src.as_ref(), dst.as_ref(), &CopyOptions::default()))
+}
+
+/// The copy failed as a whole if any entry in it did
+fn first_failure(failures: Vec<JobFailure>) -> io::Result<()> {
+	match failures.into_iter().next() {
+		Some(failure) => Err(io::Error::other(format!("{}: {}", failure.path.display(), failure.message))),
+		None => Ok(()),
+	}
+}
+
+/// Fails when `dest` is the folder `source` or lies somewhere below it, copying
+/// into it would never end. Symbolic links and bind mounts that lead back into
+/// `source` are seen through as well.
+pub fn check_destination(source: &Path, dest: &Path) -> Result<()> {
+	let Ok(metadata) = fs::symlink_metadata(source) else { return Ok(()) };
+	// Links are copied or moved as links
+	if !metadata.is_dir() {
+		return Ok(());
+	}
+	let source_path = fs::canonicalize(source).map_err(|e| Error::Metadata(source.to_path_buf(), e))?;
+	let dest = resolve(dest).map_err(|e| Error::Metadata(dest.to_path_buf(), e))?;
+
+	// The destination or one of its parents is the source under another name,
+	// or both are the same place on disk seen through different mounts
+	let source_id = platform::file_id(&metadata);
+	let inside = dest.ancestors().any(|folder| {
+		folder == source_path
+			|| source_id.is_some() && fs::metadata(folder).ok().and_then(|m| platform::file_id(&m)) == source_id
+	});
+	let mounted_inside = match (platform::mount_path(&source_path), platform::mount_path(&dest)) {
+		(Some((source_device, source_path)), Some((dest_device, dest))) => {
+			source_device == dest_device && dest.starts_with(source_path)
+		}
+		_ => false,
+	};
+	if inside || mounted_inside {
+		return Err(Error::IntoItself(source.to_path_buf()));
+	}
+	Ok(())
+}
+
+/// `path` with links and `..` resolved as far as it exists, the folders that are
+/// only about to be created are put back on the end as they are written
+fn resolve(path: &Path) -> io::Result<PathBuf> {
+	let mut missing = Vec::new();
+	let mut existing = path;
+	loop {
+		let folder = if existing.as_os_str().is_empty() { Path::new(".") } else { existing };
+		match fs::canonicalize(folder) {
+			Ok(resolved) => return Ok(missing.iter().rev().fold(resolved, |path, name| path.join(name))),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => {
+				// A missing `..` can not be resolved without knowing where its folder leads
+				let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else { return Err(e) };
+				missing.push(name);
+				existing = parent;
+			}
+			Err(e) => return Err(e),
+		}
+	}
+}
+
+/// Copies a file or a whole folder to `dest`
+pub fn copy_path(source: &Path, dest: &Path) -> Result<()> {
+	if let Some(parent) = dest.parent() {
+		check_destination(source, parent)?;
+	}
+	first_failure(copy_entry(source, dest, &CopyOptions::default()))
+		.map_err(|e| Error::Copy(source.to_path_buf(), e))
+}
+
+/// Moves a file or a whole folder to `dest`
+pub fn move_path(source: &Path, dest: &Path) -> Result<()> {
+	if let Some(parent) = dest.parent() {
+		check_destination(source, parent)?;
+	}
+	fs::rename(source, dest).map_err(|e| Error::Move(source.to_path_buf(), e))
+}
+
+/// Deletes a file or a whole folder, when the normal deletion fails
+/// it is retried with elevated privileges
+pub fn delete_path(path: &Path) -> Result<()> {
+	let is_dir = path.is_dir();
+
+	// First try normal deletion
+	let normal_result = if is_dir {
+		fs::remove_dir_all(path)
+	} else {
+		fs::remove_file(path)
+	};
+
+	if normal_result.is_ok() {
+		return Ok(());
+	}
+
+	// If normal deletion fails, let the platform try with elevation
+	platform::delete_elevated(path, is_dir)
+		.map_err(|e| Error::Delete(path.to_path_buf(), e))
+}
@@ -0,0 +1,24 @@
+// Platform specific functionality, every backend exposes the same set of functions:
+//  - is_file_hidden / is_path_hidden: hidden attribute / dot-file check
+//  - delete_elevated: deletion with elevated privileges after a normal delete failed
+//  - hardlink_id: identity of a file with several hard links, to count it only once
+//  - file_id: identity of a file or folder, whatever path or mount it is reached through
+//  - mount_path: file system and path inside it, the same for every bind mount of a folder
+//  - special_file_kind / is_sparse: entries a copy can not or should not just read through
+//  - copy_symlink / copy_ownership / set_file_times / read_xattrs / write_xattrs: what a
+//    copy keeps of the original besides its contents
+//  - clone_file / copy_file_range: copies the kernel does without the data passing through rsfiles
+//  - drop_cached: makes a verified copy read back from the disk instead of memory
+//  - sync_files: writes a batch of copies out to the disk before a journal vouches for them
+//  - command: process spawning without popping up a console window
+//  - utc_offset / format_locale_time: local time zone and regional date format
+
+#[cfg(windows)]
+mod windows;
+#[cfg(windows)]
+pub use self::windows::*;
+
+#[cfg(not(windows))]
+mod unix;
+#[cfg(not(windows))]
+pub use self::unix::*;
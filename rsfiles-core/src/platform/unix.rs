@@ -0,0 +1,384 @@
+use std::{
+	ffi::OsString,
+	fs::{self, File},
+	io,
+	os::unix::fs::{FileTypeExt, MetadataExt},
+	path::{Path, PathBuf},
+	process::Command,
+	sync::Once,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+/// On Unix a file is hidden when its name starts with a dot
+pub fn is_file_hidden(entry: &fs::DirEntry) -> io::Result<bool> {
+	let name = entry.file_name();
+	Ok(name.to_string_lossy().starts_with('.'))
+}
+
+/// Same check for a path that did not come out of a directory listing
+pub fn is_path_hidden(path: &Path) -> io::Result<bool> {
+	Ok(path.file_name().is_some_and(|name| name.to_string_lossy().starts_with('.')))
+}
+
+/// Tries to delete the path as root through polkit (pkexec)
+pub fn delete_elevated(path: &Path, is_dir: bool) -> Result<(), String> {
+	let flags = if is_dir { "-rf" } else { "-f" };
+
+	let output = command("pkexec")
+		.arg("rm")
+		.arg(flags)
+		.arg("--")
+		.arg(path)
+		.output();
+
+	match output {
+		Ok(result) => {
+			if result.status.success() && !path.exists() {
+				Ok(())
+			} else {
+				Err("File/folder still exists after elevated deletion".to_string())
+			}
+		}
+		Err(e) => Err(format!("Failed to execute pkexec: {}", e))
+	}
+}
+
+/// Device and inode of a file with more than one hard link, the same for every one of its names
+pub fn hardlink_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+	(metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+/// Device and inode, bind mounts and links to a folder share them with the folder itself
+pub fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+	Some((metadata.dev(), metadata.ino()))
+}
+
+/// Device of the file system `path` (canonical) is on and where it is inside of it,
+/// read from the mount table. A folder seen through a bind mount gives the same as
+/// the folder itself, unlike walking up its parents.
+#[cfg(target_os = "linux")]
+pub fn mount_path(path: &Path) -> Option<(String, PathBuf)> {
+	let mounts = fs::read_to_string("/proc/self/mountinfo").ok()?;
+	// "36 35 98:0 /root /mount/point options - type source": device, root inside of it, mount point.
+	// Among the mounts containing the path the deepest one wins, the later one if they are stacked.
+	let (device, root, mount_point) = mounts.lines()
+		.filter_map(|line| {
+			let mut fields = line.split(' ').skip(2);
+			Some((fields.next()?, unescape_mount_path(fields.next()?), unescape_mount_path(fields.next()?)))
+		})
+		.filter(|(_, _, mount_point)| path.starts_with(mount_point))
+		.max_by_key(|(_, _, mount_point)| mount_point.components().count())?;
+
+	let inside = path.strip_prefix(&mount_point).ok()?;
+	Some((device.to_string(), root.join(inside)))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn mount_path(_path: &Path) -> Option<(String, PathBuf)> {
+	None
+}
+
+/// The mount table writes spaces and some other bytes as octal escapes like `\040`
+#[cfg(target_os = "linux")]
+fn unescape_mount_path(field: &str) -> PathBuf {
+	use std::os::unix::ffi::OsStringExt;
+
+	let bytes = field.as_bytes();
+	let mut path = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		let escaped = bytes.get(i + 1..i + 4)
+			.filter(|_| bytes[i] == b'\\')
+			.and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+		match escaped {
+			Some(byte) => {
+				path.push(byte);
+				i += 4;
+			}
+			None => {
+				path.push(bytes[i]);
+				i += 1;
+			}
+		}
+	}
+	PathBuf::from(std::ffi::OsString::from_vec(path))
+}
+
+/// Names the kinds of entries that are no regular file, folder or link. Reading a
+/// named pipe blocks until something writes to it, so a copy skips all of them.
+pub fn special_file_kind(file_type: &fs::FileType) -> Option<&'static str> {
+	if file_type.is_fifo() {
+		Some("named pipe")
+	} else if file_type.is_socket() {
+		Some("socket")
+	} else if file_type.is_block_device() || file_type.is_char_device() {
+		Some("device")
+	} else {
+		None
+	}
+}
+
+// Smaller files are not taken for sparse: stored inline or compressed they have fewer
+// blocks than their length needs as well, and what holes they have are not worth much
+const SPARSE_MIN_SIZE: u64 = 1024 * 1024;
+
+/// Fewer blocks are allocated than the length needs, so the file has holes
+pub fn is_sparse(metadata: &fs::Metadata) -> bool {
+	metadata.len() > SPARSE_MIN_SIZE && metadata.blocks() * 512 < metadata.len()
+}
+
+/// Creates a link at `target` that points where the link `source` points
+pub fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+	std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+/// Gives `target` the owner and group of `metadata`, without following links.
+/// Only root may hand files to other users.
+pub fn copy_ownership(metadata: &fs::Metadata, target: &Path) -> io::Result<()> {
+	std::os::unix::fs::lchown(target, Some(metadata.uid()), Some(metadata.gid()))
+}
+
+/// Sets the times of a file or folder that are given, links are followed. Goes by the
+/// path, so the file does not have to be readable.
+pub fn set_file_times(path: &Path, accessed: Option<SystemTime>, modified: Option<SystemTime>) -> io::Result<()> {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	let path = CString::new(path.as_os_str().as_bytes())?;
+	let times = [timespec(accessed), timespec(modified)];
+	if unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), 0) } != 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(())
+}
+
+/// `time` for `utimensat`, one that is not given stays as it is
+fn timespec(time: Option<SystemTime>) -> libc::timespec {
+	let (seconds, nanos) = match time.map(|time| time.duration_since(UNIX_EPOCH)) {
+		None => (0, libc::UTIME_OMIT),
+		Some(Ok(after)) => (after.as_secs() as libc::time_t, after.subsec_nanos() as libc::c_long),
+		// Before 1970 the seconds count back while the nanoseconds still count forward
+		Some(Err(e)) => {
+			let before = e.duration();
+			match before.subsec_nanos() {
+				0 => (-(before.as_secs() as libc::time_t), 0),
+				nanos => (-(before.as_secs() as libc::time_t) - 1, (1_000_000_000 - nanos) as libc::c_long),
+			}
+		}
+	};
+	// Some targets have padding in it, that is zeroed
+	let mut spec: libc::timespec = unsafe { std::mem::zeroed() };
+	spec.tv_sec = seconds;
+	spec.tv_nsec = nanos;
+	spec
+}
+
+/// Extended attributes of `path` (the link itself for a link), name and value
+#[cfg(target_os = "linux")]
+pub fn read_xattrs(path: &Path) -> io::Result<Vec<(OsString, Vec<u8>)>> {
+	use std::{ffi::CString, os::unix::ffi::{OsStrExt, OsStringExt}};
+
+	let path = CString::new(path.as_os_str().as_bytes())?;
+	let names = match xattr_buffer(|buffer, size| unsafe { libc::llistxattr(path.as_ptr(), buffer.cast(), size) }) {
+		Ok(names) => names,
+		Err(e) if e.raw_os_error() == Some(libc::ENOTSUP) => return Ok(Vec::new()),
+		Err(e) => return Err(e),
+	};
+	names.split(|&byte| byte == 0)
+		.filter(|name| !name.is_empty())
+		.map(|name| {
+			let c_name = CString::new(name)?;
+			let value = xattr_buffer(|buffer, size| unsafe {
+				libc::lgetxattr(path.as_ptr(), c_name.as_ptr(), buffer.cast(), size)
+			})?;
+			Ok((OsString::from_vec(name.to_vec()), value))
+		})
+		.collect()
+}
+
+/// Asks for the size first and reads after, again if the value grew in between
+#[cfg(target_os = "linux")]
+fn xattr_buffer(read: impl Fn(*mut u8, usize) -> isize) -> io::Result<Vec<u8>> {
+	loop {
+		let size = read(std::ptr::null_mut(), 0);
+		if size < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let mut buffer = vec![0; size as usize];
+		let read_size = read(buffer.as_mut_ptr(), buffer.len());
+		if read_size >= 0 {
+			buffer.truncate(read_size as usize);
+			return Ok(buffer);
+		}
+		let error = io::Error::last_os_error();
+		if error.raw_os_error() != Some(libc::ERANGE) {
+			return Err(error);
+		}
+	}
+}
+
+/// Sets extended attributes on `path` (the link itself for a link). Namespaces only
+/// root may write to, like `trusted.`, are left out for everyone else. Fails with
+/// `Unsupported` when the file system has no extended attributes.
+#[cfg(target_os = "linux")]
+pub fn write_xattrs(path: &Path, attributes: &[(OsString, Vec<u8>)]) -> io::Result<()> {
+	use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+	let path = CString::new(path.as_os_str().as_bytes())?;
+	for (name, value) in attributes {
+		let name = CString::new(name.as_bytes())?;
+		let result = unsafe { libc::lsetxattr(path.as_ptr(), name.as_ptr(), value.as_ptr().cast(), value.len(), 0) };
+		if result < 0 {
+			let error = io::Error::last_os_error();
+			match error.raw_os_error() {
+				Some(libc::EPERM) => {}
+				Some(libc::ENOTSUP) => return Err(io::ErrorKind::Unsupported.into()),
+				_ => return Err(error),
+			}
+		}
+	}
+	Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn read_xattrs(_path: &Path) -> io::Result<Vec<(OsString, Vec<u8>)>> {
+	Ok(Vec::new())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn write_xattrs(_path: &Path, _attributes: &[(OsString, Vec<u8>)]) -> io::Result<()> {
+	Ok(())
+}
+
+/// Makes `target` share the blocks of `source` (a reflink), so nothing is copied until
+/// one of them changes. Only btrfs, XFS and a few others can, `Unsupported` elsewhere.
+#[cfg(target_os = "linux")]
+pub fn clone_file(source: &File, target: &File) -> io::Result<()> {
+	use std::os::fd::AsRawFd;
+
+	if unsafe { libc::ioctl(target.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) } == 0 {
+		return Ok(());
+	}
+	Err(unsupported_copy(io::Error::last_os_error()))
+}
+
+/// Copies up to `length` bytes from the position of `source` to the position of `target`
+/// inside the kernel, both positions move on. 0 at the end of `source`.
+#[cfg(target_os = "linux")]
+pub fn copy_file_range(source: &File, target: &File, length: usize) -> io::Result<usize> {
+	use std::os::fd::AsRawFd;
+
+	let copied = unsafe {
+		libc::copy_file_range(source.as_raw_fd(), std::ptr::null_mut(), target.as_raw_fd(), std::ptr::null_mut(), length, 0)
+	};
+	if copied < 0 {
+		return Err(unsupported_copy(io::Error::last_os_error()));
+	}
+	Ok(copied as usize)
+}
+
+/// Errors that only say this way of copying does not work between these two files
+#[cfg(target_os = "linux")]
+fn unsupported_copy(error: io::Error) -> io::Error {
+	match error.raw_os_error() {
+		Some(libc::ENOSYS | libc::EOPNOTSUPP | libc::ENOTTY | libc::EXDEV | libc::EINVAL | libc::EBADF) => {
+			io::ErrorKind::Unsupported.into()
+		}
+		_ => error,
+	}
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn clone_file(_source: &File, _target: &File) -> io::Result<()> {
+	Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn copy_file_range(_source: &File, _target: &File, _length: usize) -> io::Result<usize> {
+	Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Asks the kernel to forget the cached pages of `file`, so reading it again comes from
+/// the disk and not from memory. Only pages already written out are dropped.
+#[cfg(target_os = "linux")]
+pub fn drop_cached(file: &File) {
+	use std::os::fd::AsRawFd;
+
+	unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn drop_cached(_file: &File) {}
+
+/// Writes the files in `paths` out to the disk, renames that put them in place included.
+/// One `syncfs` per file system does that for all of them at once.
+#[cfg(target_os = "linux")]
+pub fn sync_files(paths: &[PathBuf]) -> io::Result<()> {
+	use std::os::fd::AsRawFd;
+
+	let mut synced = Vec::new();
+	for path in paths {
+		let folder = path.parent().unwrap_or(path);
+		let device = fs::metadata(folder)?.dev();
+		if synced.contains(&device) {
+			continue;
+		}
+		if unsafe { libc::syncfs(File::open(folder)?.as_raw_fd()) } != 0 {
+			return Err(io::Error::last_os_error());
+		}
+		synced.push(device);
+	}
+	Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sync_files(paths: &[PathBuf]) -> io::Result<()> {
+	if !paths.is_empty() {
+		unsafe { libc::sync() };
+	}
+	Ok(())
+}
+
+/// Creates a command for the given program, nothing special is needed on Unix
+pub fn command(program: &str) -> Command {
+	Command::new(program)
+}
+
+fn local_tm(unix_secs: i64) -> Option<libc::tm> {
+	let time = unix_secs as libc::time_t;
+	// SAFETY: localtime_r only writes into the tm we hand it
+	unsafe {
+		let mut tm: libc::tm = std::mem::zeroed();
+		if libc::localtime_r(&time, &mut tm).is_null() {
+			None
+		} else {
+			Some(tm)
+		}
+	}
+}
+
+/// Offset of local time from UTC in seconds at the given moment, daylight saving included
+#[allow(clippy::unnecessary_cast)] // tm_gmtoff is a c_long, only 64 bit on some targets
+pub fn utc_offset(unix_secs: i64) -> i64 {
+	local_tm(unix_secs).map_or(0, |tm| tm.tm_gmtoff as i64)
+}
+
+/// Short date and time in the format of the LC_TIME locale
+pub fn format_locale_time(unix_secs: i64) -> Option<String> {
+	static LOCALE: Once = Once::new();
+	// SAFETY: called once before the first strftime, the C locale is used until then
+	LOCALE.call_once(|| unsafe {
+		libc::setlocale(libc::LC_TIME, c"".as_ptr());
+	});
+
+	let tm = local_tm(unix_secs)?;
+	let mut buffer = [0u8; 128];
+	// SAFETY: strftime never writes more than the given buffer length
+	let len = unsafe {
+		libc::strftime(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len(), c"%x %X".as_ptr(), &tm)
+	};
+	if len == 0 {
+		None
+	} else {
+		Some(String::from_utf8_lossy(&buffer[..len]).into_owned())
+	}
+}
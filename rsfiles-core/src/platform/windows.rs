@@ -0,0 +1,336 @@
+use std::{
+	ffi::OsString,
+	fs::{self, File, FileTimes},
+	io,
+	path::{Path, PathBuf},
+	process::Command,
+	time::SystemTime,
+};
+use std::os::windows::{
+	fs::{MetadataExt, OpenOptionsExt},
+	process::CommandExt,
+};
+use winapi::{
+	shared::minwindef::FILETIME,
+	um::{
+		datetimeapi::{GetDateFormatW, GetTimeFormatW},
+		minwinbase::SYSTEMTIME,
+		timezoneapi::{FileTimeToSystemTime, SystemTimeToFileTime, SystemTimeToTzSpecificLocalTime},
+		winbase::CREATE_NO_WINDOW,
+		winnt::LOCALE_USER_DEFAULT,
+	},
+};
+
+const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+const FILE_ATTRIBUTE_SPARSE_FILE: u32 = 0x200;
+// Needed to open a folder instead of a file
+const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+
+// 100ns intervals between 1601-01-01 (FILETIME epoch) and 1970-01-01
+const FILETIME_UNIX_EPOCH: i64 = 116_444_736_000_000_000;
+const FILETIME_TICKS_PER_SECOND: i64 = 10_000_000;
+
+// Flags of GetDateFormatW / GetTimeFormatW, not part of winapi
+const DATE_SHORTDATE: u32 = 0x1;
+const TIME_NOSECONDS: u32 = 0x2;
+
+/// On Windows, check the hidden file attribute
+pub fn is_file_hidden(entry: &fs::DirEntry) -> io::Result<bool> {
+	let metadata = entry.metadata()?;
+	Ok(metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+}
+
+/// Same check for a path that did not come out of a directory listing
+pub fn is_path_hidden(path: &Path) -> io::Result<bool> {
+	let metadata = fs::symlink_metadata(path)?;
+	Ok(metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+}
+
+/// The link count and file index need an open handle on Windows, which is too slow
+/// for every file of a walk, so hard links are counted once per name
+pub fn hardlink_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+	None
+}
+
+/// Needs an open handle like `hardlink_id`, links and junctions are resolved by
+/// canonicalizing the path instead
+pub fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+	None
+}
+
+/// Drives mounted into folders are resolved by canonicalizing the path as well
+pub fn mount_path(_path: &Path) -> Option<(String, PathBuf)> {
+	None
+}
+
+/// Pipes and devices don't show up in folders on Windows
+pub fn special_file_kind(_file_type: &fs::FileType) -> Option<&'static str> {
+	None
+}
+
+pub fn is_sparse(metadata: &fs::Metadata) -> bool {
+	metadata.file_attributes() & FILE_ATTRIBUTE_SPARSE_FILE != 0
+}
+
+/// Creates a link at `target` that points where the link `source` points,
+/// which needs developer mode or administrator rights
+pub fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+	let link = fs::read_link(source)?;
+	if fs::metadata(source).is_ok_and(|metadata| metadata.is_dir()) {
+		std::os::windows::fs::symlink_dir(link, target)
+	} else {
+		std::os::windows::fs::symlink_file(link, target)
+	}
+}
+
+/// Files belong to whoever creates them, changing that needs the security API
+pub fn copy_ownership(_metadata: &fs::Metadata, _target: &Path) -> io::Result<()> {
+	Ok(())
+}
+
+/// Sets the times of a file or folder that are given, links are followed
+pub fn set_file_times(path: &Path, accessed: Option<SystemTime>, modified: Option<SystemTime>) -> io::Result<()> {
+	let mut times = FileTimes::new();
+	if let Some(accessed) = accessed {
+		times = times.set_accessed(accessed);
+	}
+	if let Some(modified) = modified {
+		times = times.set_modified(modified);
+	}
+	fs::OpenOptions::new()
+		.write(true)
+		.custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+		.open(path)?
+		.set_times(times)
+}
+
+/// Alternate data streams are not copied
+pub fn read_xattrs(_path: &Path) -> io::Result<Vec<(OsString, Vec<u8>)>> {
+	Ok(Vec::new())
+}
+
+pub fn write_xattrs(_path: &Path, _attributes: &[(OsString, Vec<u8>)]) -> io::Result<()> {
+	Ok(())
+}
+
+/// Block cloning on ReFS is not used, files are always copied
+pub fn clone_file(_source: &File, _target: &File) -> io::Result<()> {
+	Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn copy_file_range(_source: &File, _target: &File, _length: usize) -> io::Result<usize> {
+	Err(io::ErrorKind::Unsupported.into())
+}
+
+/// Reading a file back right after writing it comes from the cache here
+pub fn drop_cached(_file: &File) {}
+
+/// Writes the files in `paths` out to the disk one by one, flushing a whole volume
+/// needs administrator rights
+pub fn sync_files(paths: &[PathBuf]) -> io::Result<()> {
+	for path in paths {
+		fs::OpenOptions::new().write(true).open(path)?.sync_all()?;
+	}
+	Ok(())
+}
+
+/// Tries to delete the path with elevation using PowerShell, falls back to cmd
+pub fn delete_elevated(path: &Path, is_dir: bool) -> Result<(), String> {
+	let path_str = path.to_string_lossy().to_string();
+	
+	// Use PowerShell's Remove-Item with proper path handling
+	let ps_script = if is_dir {
+		format!("Remove-Item -LiteralPath '{}' -Recurse -Force -ErrorAction Stop", path_str.replace("'", "''"))
+	} else {
+		format!("Remove-Item -LiteralPath '{}' -Force -ErrorAction Stop", path_str.replace("'", "''"))
+	};
+
+	// Run PowerShell with elevation
+	let output = command("powershell")
+		.args([
+			"-Command", 
+			&format!("Start-Process powershell -ArgumentList '-Command', '{}' -Verb RunAs -WindowStyle Hidden -Wait", ps_script.replace("'", "''"))
+		])
+		.output();
+
+	match output {
+		Ok(result) => {
+			if result.status.success() && !path.exists() {
+				Ok(())
+			} else {
+				// If PowerShell elevation fails, try alternative method
+				force_delete_alternative(path, is_dir)
+			}
+		}
+		Err(_) => {
+			// If PowerShell fails, try alternative method
+			force_delete_alternative(path, is_dir)
+		}
+	}
+}
+
+/// Creates a command that does not pop up a console window
+pub fn command(program: &str) -> Command {
+	let mut command = Command::new(program);
+	command.creation_flags(CREATE_NO_WINDOW);
+	command
+}
+
+// Alternative force delete method with better command construction
+fn force_delete_alternative(path: &Path, is_dir: bool) -> Result<(), String> {
+	let path_str = path.to_string_lossy().to_string();
+	
+	// Method 1: Try elevated cmd commands with proper escaping
+	let result = try_cmd_delete(&path_str, is_dir);
+	if result.is_ok() && !path.exists() {
+		return Ok(());
+	}
+
+	// Method 2: Try PowerShell direct execution with elevation
+	let result = try_powershell_direct(&path_str, is_dir);
+	if result.is_ok() && !path.exists() {
+		return Ok(());
+	}
+
+	// Final check
+	if path.exists() {
+		Err("File/folder still exists after all deletion attempts".to_string())
+	} else {
+		Ok(())
+	}
+}
+
+fn try_cmd_delete(path_str: &str, is_dir: bool) -> Result<(), String> {
+	// Use cmd with proper elevation request
+	let script_content = if is_dir {
+		format!(
+			"@echo off\ntakeown /f \"{}\" /r /d y >nul 2>&1\nicacls \"{}\" /grant administrators:F /t >nul 2>&1\nrmdir /s /q \"{}\"",
+			path_str, path_str, path_str
+		)
+	} else {
+		format!(
+			"@echo off\ntakeown /f \"{}\" >nul 2>&1\nicacls \"{}\" /grant administrators:F >nul 2>&1\ndel /f /q \"{}\"",
+			path_str, path_str, path_str
+		)
+	};
+
+	// Create a temporary batch file
+	let temp_dir = std::env::temp_dir();
+	let batch_file = temp_dir.join("delete_temp.bat");
+	
+	if let Err(e) = fs::write(&batch_file, script_content) {
+		return Err(format!("Failed to create batch file: {}", e));
+	}
+
+	let batch_path = batch_file.to_string_lossy().to_string();
+	
+	// Execute with elevation
+	let output = command("powershell")
+		.args([
+			"-Command", 
+			&format!("Start-Process cmd -ArgumentList '/c', '\"{}\"' -Verb RunAs -WindowStyle Hidden -Wait", batch_path)
+		])
+		.output();
+
+	// Clean up batch file
+	let _ = fs::remove_file(&batch_file);
+
+	match output {
+		Ok(result) => {
+			if result.status.success() {
+				Ok(())
+			} else {
+				Err("Batch command failed".to_string())
+			}
+		}
+		Err(e) => Err(format!("Failed to execute batch command: {}", e))
+	}
+}
+
+fn try_powershell_direct(path_str: &str, is_dir: bool) -> Result<(), String> {
+	// Try direct PowerShell execution as administrator
+	let ps_command = if is_dir {
+		format!("Remove-Item -Path '{}' -Recurse -Force", path_str.replace("'", "''"))
+	} else {
+		format!("Remove-Item -Path '{}' -Force", path_str.replace("'", "''"))
+	};
+
+	let output = command("powershell")
+		.args([
+			"-Command",
+			&format!("Start-Process powershell -ArgumentList '-ExecutionPolicy', 'Bypass', '-Command', '{}' -Verb RunAs -WindowStyle Hidden -Wait", ps_command.replace("'", "''"))
+		])
+		.output();
+
+	match output {
+		Ok(result) => {
+			if result.status.success() {
+				Ok(())
+			} else {
+				Err("Direct PowerShell command failed".to_string())
+			}
+		}
+		Err(e) => Err(format!("Failed to execute PowerShell command: {}", e))
+	}
+}
+
+fn to_filetime(unix_secs: i64) -> FILETIME {
+	let ticks = (unix_secs * FILETIME_TICKS_PER_SECOND + FILETIME_UNIX_EPOCH) as u64;
+	FILETIME { dwLowDateTime: ticks as u32, dwHighDateTime: (ticks >> 32) as u32 }
+}
+
+fn from_filetime(filetime: &FILETIME) -> i64 {
+	let ticks = ((filetime.dwHighDateTime as u64) << 32) | filetime.dwLowDateTime as u64;
+	(ticks as i64 - FILETIME_UNIX_EPOCH) / FILETIME_TICKS_PER_SECOND
+}
+
+/// Local wall clock time of the moment, using the time zone rules that applied back then
+fn local_system_time(unix_secs: i64) -> Option<SYSTEMTIME> {
+	let filetime = to_filetime(unix_secs);
+	// SAFETY: all pointers point to properly sized locals
+	unsafe {
+		let mut utc: SYSTEMTIME = std::mem::zeroed();
+		let mut local: SYSTEMTIME = std::mem::zeroed();
+		if FileTimeToSystemTime(&filetime, &mut utc) == 0
+			|| SystemTimeToTzSpecificLocalTime(std::ptr::null(), &utc, &mut local) == 0 {
+			return None;
+		}
+		Some(local)
+	}
+}
+
+/// Offset of local time from UTC in seconds at the given moment, daylight saving included
+pub fn utc_offset(unix_secs: i64) -> i64 {
+	let Some(local) = local_system_time(unix_secs) else { return 0 };
+	// SAFETY: both pointers point to locals
+	unsafe {
+		let mut local_filetime: FILETIME = std::mem::zeroed();
+		if SystemTimeToFileTime(&local, &mut local_filetime) == 0 {
+			return 0;
+		}
+		from_filetime(&local_filetime) - unix_secs
+	}
+}
+
+/// Short date and time in the format of the user's regional settings
+pub fn format_locale_time(unix_secs: i64) -> Option<String> {
+	let local = local_system_time(unix_secs)?;
+	let mut date = [0u16; 64];
+	let mut time = [0u16; 64];
+	// SAFETY: the buffer lengths are passed along, null format means locale default
+	let (date_len, time_len) = unsafe {
+		(
+			GetDateFormatW(LOCALE_USER_DEFAULT, DATE_SHORTDATE, &local, std::ptr::null(), date.as_mut_ptr(), date.len() as i32),
+			GetTimeFormatW(LOCALE_USER_DEFAULT, TIME_NOSECONDS, &local, std::ptr::null(), time.as_mut_ptr(), time.len() as i32),
+		)
+	};
+	if date_len <= 0 || time_len <= 0 {
+		return None;
+	}
+	// Lengths include the terminating null
+	Some(format!(
+		"{} {}",
+		String::from_utf16_lossy(&date[..date_len as usize - 1]),
+		String::from_utf16_lossy(&time[..time_len as usize - 1])
+	))
+}
@@ -0,0 +1,249 @@
+use crate::{Error, Result};
+use std::{fs, io, path::Path, path::PathBuf};
+
+// Layout of the binary format is described in [MS-SHLLINK]
+const HEADER_SIZE: usize = 0x4C;
+const LINK_CLSID: [u8; 16] = [
+	0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+// LinkFlags
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x0000_0001;
+const HAS_LINK_INFO: u32 = 0x0000_0002;
+const HAS_NAME: u32 = 0x0000_0004;
+const HAS_RELATIVE_PATH: u32 = 0x0000_0008;
+const HAS_WORKING_DIR: u32 = 0x0000_0010;
+const HAS_ARGUMENTS: u32 = 0x0000_0020;
+const HAS_ICON_LOCATION: u32 = 0x0000_0040;
+const IS_UNICODE: u32 = 0x0000_0080;
+const FORCE_NO_LINK_INFO: u32 = 0x0000_0100;
+
+// LinkInfoFlags
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x1;
+const COMMON_NETWORK_RELATIVE_LINK: u32 = 0x2;
+
+// CommonNetworkRelativeLinkFlags
+const VALID_DEVICE: u32 = 0x1;
+
+/// Everything rsfiles reads out of a Windows shell link (`.lnk`) file
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShortcutInfo {
+	/// Target path on a local volume, e.g. `C:\Windows`
+	pub local_base_path: Option<String>,
+	/// Share the target lives on, e.g. `\\server\share`
+	pub network_path: Option<String>,
+	/// Drive letter the share was mapped to, e.g. `Z:`
+	pub device_name: Option<String>,
+	/// Rest of the target path after the local base path or share
+	pub common_path_suffix: Option<String>,
+	/// Description of the shortcut
+	pub name: Option<String>,
+	/// Target relative to the location of the `.lnk` file
+	pub relative_path: Option<String>,
+	pub working_dir: Option<String>,
+	pub arguments: Option<String>,
+	pub icon_location: Option<String>,
+}
+
+impl ShortcutInfo {
+	/// Reads and parses the shortcut file at `path`
+	pub fn from_path(path: &Path) -> Result<Self> {
+		fs::read(path)
+			.and_then(|data| Self::parse(&data))
+			.map_err(|e| Error::Shortcut(path.to_path_buf(), e))
+	}
+
+	/// Parses the raw contents of a shortcut file
+	pub fn parse(data: &[u8]) -> io::Result<Self> {
+		if read_u32(data, 0)? as usize != HEADER_SIZE || data.get(4..20) != Some(&LINK_CLSID[..]) {
+			return Err(invalid("not a shell link file"));
+		}
+
+		let flags = read_u32(data, 20)?;
+		let mut info = ShortcutInfo::default();
+		let mut pos = HEADER_SIZE;
+
+		if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+			// The item id list is only meaningful to the Windows shell, skip it
+			pos += 2 + read_u16(data, pos)? as usize;
+		}
+
+		if flags & HAS_LINK_INFO != 0 {
+			let size = read_u32(data, pos)? as usize;
+			let link_info = data.get(pos..pos + size)
+				.ok_or_else(|| invalid("LinkInfo is out of bounds"))?;
+			if flags & FORCE_NO_LINK_INFO == 0 {
+				info.read_link_info(link_info)?;
+			}
+			pos += size;
+		}
+
+		let unicode = flags & IS_UNICODE != 0;
+		let string_data = [
+			(HAS_NAME, &mut info.name),
+			(HAS_RELATIVE_PATH, &mut info.relative_path),
+			(HAS_WORKING_DIR, &mut info.working_dir),
+			(HAS_ARGUMENTS, &mut info.arguments),
+			(HAS_ICON_LOCATION, &mut info.icon_location),
+		];
+		for (flag, field) in string_data {
+			if flags & flag != 0 {
+				*field = Some(read_counted_string(data, &mut pos, unicode)?);
+			}
+		}
+
+		Ok(info)
+	}
+
+	fn read_link_info(&mut self, link_info: &[u8]) -> io::Result<()> {
+		let header_size = read_u32(link_info, 4)?;
+		let link_flags = read_u32(link_info, 8)?;
+		let has_unicode = header_size >= 0x24;
+
+		if link_flags & VOLUME_ID_AND_LOCAL_BASE_PATH != 0 {
+			self.local_base_path = Some(if has_unicode {
+				read_wide_c_string(link_info, read_u32(link_info, 0x1C)? as usize)?
+			} else {
+				read_c_string(link_info, read_u32(link_info, 0x10)? as usize)?
+			});
+		}
+
+		if link_flags & COMMON_NETWORK_RELATIVE_LINK != 0 {
+			let offset = read_u32(link_info, 0x14)? as usize;
+			let network_link = link_info.get(offset..)
+				.ok_or_else(|| invalid("CommonNetworkRelativeLink is out of bounds"))?;
+			self.read_network_link(network_link)?;
+		}
+
+		let suffix = if has_unicode {
+			read_wide_c_string(link_info, read_u32(link_info, 0x20)? as usize)?
+		} else {
+			read_c_string(link_info, read_u32(link_info, 0x18)? as usize)?
+		};
+		self.common_path_suffix = Some(suffix).filter(|suffix| !suffix.is_empty());
+
+		Ok(())
+	}
+
+	fn read_network_link(&mut self, network_link: &[u8]) -> io::Result<()> {
+		let flags = read_u32(network_link, 4)?;
+		let net_name_offset = read_u32(network_link, 8)? as usize;
+		let device_name_offset = read_u32(network_link, 12)? as usize;
+		let has_unicode = net_name_offset > 0x14;
+
+		self.network_path = Some(if has_unicode {
+			read_wide_c_string(network_link, read_u32(network_link, 0x14)? as usize)?
+		} else {
+			read_c_string(network_link, net_name_offset)?
+		});
+
+		if flags & VALID_DEVICE != 0 {
+			self.device_name = Some(if has_unicode {
+				read_wide_c_string(network_link, read_u32(network_link, 0x18)? as usize)?
+			} else {
+				read_c_string(network_link, device_name_offset)?
+			});
+		}
+
+		Ok(())
+	}
+
+	/// The absolute target path as Windows wrote it, without any conversion
+	pub fn target(&self) -> Option<String> {
+		let suffix = self.common_path_suffix.as_deref().unwrap_or_default();
+		if let Some(base) = &self.local_base_path {
+			Some(format!("{}{}", base, suffix))
+		} else if let Some(share) = &self.network_path {
+			if suffix.is_empty() {
+				Some(share.clone())
+			} else {
+				Some(format!("{}\\{}", share.trim_end_matches('\\'), suffix))
+			}
+		} else {
+			None
+		}
+	}
+
+	/// Finds the path the shortcut at `lnk_path` points to on this machine.
+	/// The absolute target is preferred, but shortcuts copied from another
+	/// machine (backups, network shares) usually only work through the relative path.
+	/// When neither exists the first candidate is returned so it can be reported.
+	pub fn resolve(&self, lnk_path: &Path) -> Option<PathBuf> {
+		let base_dir = lnk_path.parent().unwrap_or(Path::new(""));
+		let candidates = [
+			self.target().map(|target| native_path(&target)),
+			self.relative_path.as_deref().map(|relative| base_dir.join(native_path(relative))),
+		];
+
+		candidates.iter()
+			.flatten()
+			.find(|path| path.exists())
+			.or_else(|| candidates.iter().flatten().next())
+			.cloned()
+	}
+}
+
+/// Converts a path stored in a shortcut into one the current platform understands
+fn native_path(path: &str) -> PathBuf {
+	if cfg!(windows) {
+		PathBuf::from(path)
+	} else {
+		PathBuf::from(path.replace('\\', "/"))
+	}
+}
+
+fn invalid(message: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> io::Result<u16> {
+	data.get(offset..offset + 2)
+		.map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+		.ok_or_else(|| invalid("unexpected end of shortcut data"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> io::Result<u32> {
+	data.get(offset..offset + 4)
+		.map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+		.ok_or_else(|| invalid("unexpected end of shortcut data"))
+}
+
+/// Null terminated string in the system code page, read as Latin-1
+fn read_c_string(data: &[u8], offset: usize) -> io::Result<String> {
+	let bytes = data.get(offset..).ok_or_else(|| invalid("string is out of bounds"))?;
+	let len = bytes.iter().position(|&b| b == 0)
+		.ok_or_else(|| invalid("string is not terminated"))?;
+	Ok(bytes[..len].iter().map(|&b| b as char).collect())
+}
+
+/// Null terminated UTF-16LE string
+fn read_wide_c_string(data: &[u8], offset: usize) -> io::Result<String> {
+	let bytes = data.get(offset..).ok_or_else(|| invalid("string is out of bounds"))?;
+	let units: Vec<u16> = bytes.chunks_exact(2)
+		.map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+		.collect();
+	let len = units.iter().position(|&unit| unit == 0)
+		.ok_or_else(|| invalid("string is not terminated"))?;
+	Ok(String::from_utf16_lossy(&units[..len]))
+}
+
+/// StringData entry: a character count followed by the characters, no terminator
+fn read_counted_string(data: &[u8], pos: &mut usize, unicode: bool) -> io::Result<String> {
+	let count = read_u16(data, *pos)? as usize;
+	*pos += 2;
+
+	let byte_len = if unicode { count * 2 } else { count };
+	let bytes = data.get(*pos..*pos + byte_len)
+		.ok_or_else(|| invalid("StringData is out of bounds"))?;
+	*pos += byte_len;
+
+	Ok(if unicode {
+		let units: Vec<u16> = bytes.chunks_exact(2)
+			.map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+			.collect();
+		String::from_utf16_lossy(&units)
+	} else {
+		bytes.iter().map(|&b| b as char).collect()
+	})
+}
@@ -0,0 +1,126 @@
+use crate::{Collation, FileEntry};
+use std::{cmp::Ordering, fmt};
+
+/// Column the file list can be sorted by
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SortColumn {
+	Name,
+	Modified,
+	Size,
+	/// Shortcuts and links first, then by extension
+	Type,
+}
+
+impl SortColumn {
+	pub const ALL: [SortColumn; 4] = [SortColumn::Name, SortColumn::Modified, SortColumn::Size, SortColumn::Type];
+}
+
+impl fmt::Display for SortColumn {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			SortColumn::Name => "Name",
+			SortColumn::Modified => "Modified",
+			SortColumn::Size => "Size",
+			SortColumn::Type => "Type",
+		})
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SortOrder {
+	pub column: SortColumn,
+	pub descending: bool,
+	/// Keep directories above everything else, no matter the direction
+	pub folders_first: bool,
+	/// Decides between entries the main column sees as equal, always ascending
+	pub secondary: SortColumn,
+	/// How names and extensions are compared
+	pub collation: Collation,
+}
+
+impl Default for SortOrder {
+	/// Directories, then shortcuts, then files by extension and name
+	fn default() -> Self {
+		Self {
+			column: SortColumn::Type,
+			descending: false,
+			folders_first: true,
+			secondary: SortColumn::Name,
+			collation: Collation::Natural,
+		}
+	}
+}
+
+impl SortOrder {
+	/// Sorting by the same column again flips the direction, a new column starts ascending
+	pub fn toggled(self, column: SortColumn) -> Self {
+		if self.column == column {
+			Self { descending: !self.descending, ..self }
+		} else {
+			Self { column, descending: false, ..self }
+		}
+	}
+
+	pub fn compare(&self, a: &FileEntry, b: &FileEntry) -> Ordering {
+		let folders = if self.folders_first {
+			b.is_dir().cmp(&a.is_dir())
+		} else {
+			Ordering::Equal
+		};
+
+		let primary = compare_by(self.column, self.collation, a, b);
+		let primary = if self.descending { primary.reverse() } else { primary };
+
+		folders
+			.then(primary)
+			.then_with(|| compare_by(self.secondary, self.collation, a, b))
+			.then_with(|| compare_by(SortColumn::Name, self.collation, a, b))
+			.then_with(|| a.display_name().cmp(b.display_name()))
+	}
+}
+
+/// Names and extensions go by the keys the entries built for them
+fn compare_by(column: SortColumn, collation: Collation, a: &FileEntry, b: &FileEntry) -> Ordering {
+	match (column, collation) {
+		(SortColumn::Name, Collation::Natural) => a.name_key().cmp(b.name_key()),
+		(SortColumn::Name, Collation::Bytes) => a.display_name().as_bytes().cmp(b.display_name().as_bytes()),
+		(SortColumn::Modified, _) => a.modified().cmp(&b.modified()),
+		(SortColumn::Size, _) => a.size().cmp(&b.size()),
+		(SortColumn::Type, _) => type_rank(a).cmp(&type_rank(b)).then_with(|| match collation {
+			Collation::Natural => a.extension_key().cmp(b.extension_key()),
+			Collation::Bytes => a.name_extension().as_bytes().cmp(b.name_extension().as_bytes()),
+		}),
+	}
+}
+
+fn type_rank(file: &FileEntry) -> u8 {
+	if file.is_dir() { 0 } else if file.is_shortcut() || file.is_symlink() { 1 } else { 2 }
+}
+
+/// Sorts directory contents by the given order, the default one is:
+/// 1. Directories and links to directories (sorted by name, see `natural_cmp`)
+/// 2. Shortcuts and links to files (sorted by name)
+/// 3. Other files (sorted by extension, then by name)
+pub fn sort_directory_contents(files: &mut [FileEntry], order: &SortOrder) {
+	files.sort_by(|a, b| order.compare(a, b));
+}
+
+/// Sorts `batch` and merges it into the already sorted `files`
+pub fn merge_sorted(files: &mut Vec<FileEntry>, mut batch: Vec<FileEntry>, order: &SortOrder) {
+	sort_directory_contents(&mut batch, order);
+
+	let existing = std::mem::take(files);
+	files.reserve(existing.len() + batch.len());
+
+	let mut existing = existing.into_iter().peekable();
+	let mut batch = batch.into_iter().peekable();
+	while let (Some(a), Some(b)) = (existing.peek(), batch.peek()) {
+		if order.compare(b, a) == Ordering::Less {
+			files.extend(batch.next());
+		} else {
+			files.extend(existing.next());
+		}
+	}
+	files.extend(existing);
+	files.extend(batch);
+}
@@ -0,0 +1,129 @@
+use crate::{Error, Result};
+use notify::{
+	event::{ModifyKind, RenameMode},
+	Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
+};
+use std::{
+	path::{Path, PathBuf},
+	sync::mpsc::{self, RecvTimeoutError},
+	thread,
+	time::{Duration, Instant},
+};
+
+// A batch is sent at the latest this long after its first event, even if events keep coming
+const MAX_DELAY: Duration = Duration::from_secs(1);
+// Past this many changes in one batch reading the directory again is cheaper
+const MAX_CHANGES: usize = 4096;
+
+/// A change to a direct child of a watched directory
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FsChange {
+	Created(PathBuf),
+	Removed(PathBuf),
+	Modified(PathBuf),
+	Renamed(PathBuf, PathBuf),
+}
+
+impl FsChange {
+	/// Paths whose entry may look different now
+	pub fn paths(&self) -> impl Iterator<Item = &PathBuf> {
+		let (first, second) = match self {
+			FsChange::Created(path) | FsChange::Removed(path) | FsChange::Modified(path) => (path, None),
+			FsChange::Renamed(from, to) => (from, Some(to)),
+		};
+		std::iter::once(first).chain(second)
+	}
+}
+
+/// Changes collected while events kept coming in
+#[derive(Clone, Debug, Default)]
+pub struct ChangeBatch {
+	pub changes: Vec<FsChange>,
+	/// Events were lost, there were too many or the directory itself changed,
+	/// only reading the whole listing again gives the right state
+	pub rescan: bool,
+}
+
+impl ChangeBatch {
+	fn add(&mut self, dir: &Path, event: notify::Result<Event>) {
+		if self.rescan {
+			return;
+		}
+		let event = match event {
+			Ok(event) if !event.need_rescan() => event,
+			_ => {
+				self.rescan = true;
+				return;
+			}
+		};
+
+		if event.paths.iter().any(|path| path == dir) {
+			// Watching a removed or moved directory gives nothing useful anymore
+			self.rescan |= matches!(event.kind, EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_)));
+			return;
+		}
+		let mut paths = event.paths.into_iter().filter(|path| path.parent() == Some(dir));
+
+		match event.kind {
+			EventKind::Access(_) => {}
+			EventKind::Create(_) => self.changes.extend(paths.map(FsChange::Created)),
+			EventKind::Remove(_) => self.changes.extend(paths.map(FsChange::Removed)),
+			EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+				if let (Some(from), Some(to)) = (paths.next(), paths.next()) {
+					self.changes.push(FsChange::Renamed(from, to));
+				}
+			}
+			EventKind::Modify(ModifyKind::Name(RenameMode::From)) => self.changes.extend(paths.map(FsChange::Removed)),
+			EventKind::Modify(ModifyKind::Name(RenameMode::To)) => self.changes.extend(paths.map(FsChange::Created)),
+			_ => self.changes.extend(paths.map(FsChange::Modified)),
+		}
+
+		if self.changes.len() > MAX_CHANGES {
+			self.changes.clear();
+			self.rescan = true;
+		}
+	}
+}
+
+/// Watches a directory until it is dropped
+pub struct DirectoryWatcher {
+	_watcher: RecommendedWatcher,
+}
+
+/// Starts watching the direct children of `path`. Events are collected until
+/// none arrived for `debounce` (or for at most a second) and handed to `on_changes`
+/// as one batch, on a background thread. Returning `false` from it stops the watch.
+pub fn watch_directory<F>(path: &Path, debounce: Duration, mut on_changes: F) -> Result<DirectoryWatcher>
+where
+	F: FnMut(ChangeBatch) -> bool + Send + 'static,
+{
+	let (sender, events) = mpsc::channel();
+	let mut watcher = notify::recommended_watcher(sender)
+		.map_err(|e| Error::Watch(path.to_path_buf(), e.to_string()))?;
+	watcher.watch(path, RecursiveMode::NonRecursive)
+		.map_err(|e| Error::Watch(path.to_path_buf(), e.to_string()))?;
+
+	let dir = path.to_path_buf();
+	// Ends once the watcher is dropped, that closes the event channel
+	thread::spawn(move || {
+		while let Ok(event) = events.recv() {
+			let mut batch = ChangeBatch::default();
+			batch.add(&dir, event);
+
+			let deadline = Instant::now() + MAX_DELAY;
+			while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+				match events.recv_timeout(debounce.min(left)) {
+					Ok(event) => batch.add(&dir, event),
+					Err(RecvTimeoutError::Timeout) => break,
+					Err(RecvTimeoutError::Disconnected) => return,
+				}
+			}
+
+			if (batch.rescan || !batch.changes.is_empty()) && !on_changes(batch) {
+				return;
+			}
+		}
+	});
+
+	Ok(DirectoryWatcher { _watcher: watcher })
+}
@@ -0,0 +1,49 @@
+//! Helpers shared by the integration tests
+
+use std::{
+	fs,
+	ops::Deref,
+	path::{Path, PathBuf},
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+// Tells apart directories of tests running at the same time in one process
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// Fresh directory under the system temp dir, removed again once it is dropped,
+/// also when the test fails halfway
+pub struct Scratch(PathBuf);
+
+impl Scratch {
+	/// With an empty folder for each of `folders` in it
+	pub fn new(test: &str, folders: &[&str]) -> Self {
+		let unique = NEXT.fetch_add(1, Ordering::Relaxed);
+		let dir = std::env::temp_dir().join(format!("rsfiles-{}-{}-{}", test, std::process::id(), unique));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		for folder in folders {
+			fs::create_dir_all(dir.join(folder)).unwrap();
+		}
+		Self(dir)
+	}
+}
+
+impl Deref for Scratch {
+	type Target = Path;
+
+	fn deref(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl AsRef<Path> for Scratch {
+	fn as_ref(&self) -> &Path {
+		&self.0
+	}
+}
+
+impl Drop for Scratch {
+	fn drop(&mut self) {
+		let _ = fs::remove_dir_all(&self.0);
+	}
+}
@@ -0,0 +1,66 @@
+use rsfiles_core::{copy_entry, run_job, ConflictPolicy, CopyOptions, Job, JobControl};
+use std::fs;
+
+mod common;
+use common::Scratch;
+
+/// Contents that differ from file to file, so a mix-up between workers shows
+fn contents(index: usize) -> Vec<u8> {
+	format!("file number {}\n", index).repeat(index % 50 + 1).into_bytes()
+}
+
+#[test]
+fn many_small_files_on_several_workers() {
+	let dir = Scratch::new("fast-small", &["source"]);
+	let source = dir.join("source");
+	// More than one batch, spread over a few folders
+	for index in 0..700 {
+		let folder = source.join(format!("folder{}", index % 7));
+		fs::create_dir_all(&folder).unwrap();
+		fs::write(folder.join(format!("{}.txt", index)), contents(index)).unwrap();
+	}
+
+	let options = CopyOptions { workers: 4, ..CopyOptions::default() };
+	let job = Job::Copy { sources: vec![source.clone()], dest: dir.join("target"), conflicts: ConflictPolicy::new(), options };
+	fs::create_dir(dir.join("target")).unwrap();
+	let outcome = run_job(&job, &JobControl::new(), |_| {});
+
+	assert_eq!(outcome.failures, vec![]);
+	assert_eq!(outcome.progress.files_done, 700);
+	assert_eq!(outcome.progress.bytes_done, outcome.progress.bytes_total);
+	for index in 0..700 {
+		let copy = dir.join(format!("target/source/folder{}/{}.txt", index % 7, index));
+		assert_eq!(fs::read(copy).unwrap(), contents(index), "{}", index);
+	}
+}
+
+#[test]
+fn kernel_copy_and_plain_copy_give_the_same_file() {
+	let dir = Scratch::new("fast-large", &["source"]);
+	// A few chunks and a bit, with no runs of zeros
+	let data: Vec<u8> = (0..3 * 1024 * 1024 + 12345).map(|i: u32| (i % 251) as u8 + 1).collect();
+	let file = dir.join("source/large.bin");
+	fs::write(&file, &data).unwrap();
+
+	for kernel_copy in [true, false] {
+		let copy = dir.join(format!("copy-{}.bin", kernel_copy));
+		let options = CopyOptions { kernel_copy, ..CopyOptions::default() };
+		assert_eq!(copy_entry(&file, &copy, &options), vec![]);
+		assert_eq!(fs::read(&copy).unwrap(), data, "kernel copy {}", kernel_copy);
+	}
+}
+
+#[test]
+fn one_worker_copies_one_file_after_the_other() {
+	let dir = Scratch::new("fast-sequential", &["source"]);
+	let source = dir.join("source");
+	for index in 0..20 {
+		fs::write(source.join(format!("{}.txt", index)), contents(index)).unwrap();
+	}
+
+	let options = CopyOptions { workers: 1, kernel_copy: false, ..CopyOptions::default() };
+	assert_eq!(copy_entry(&source, &dir.join("target"), &options), vec![]);
+	for index in 0..20 {
+		assert_eq!(fs::read(dir.join(format!("target/{}.txt", index))).unwrap(), contents(index));
+	}
+}
@@ -0,0 +1,124 @@
+use rsfiles_core::{check_destination, copy_path, move_path, run_job, ConflictPolicy, CopyOptions, Error, Job, JobControl};
+use std::{fs, path::{Path, PathBuf}};
+
+mod common;
+use common::Scratch;
+
+/// Scratch directory with `source/inner/deep` and a file in it
+fn tree(test: &str) -> Scratch {
+	let dir = Scratch::new(test, &["source/inner/deep"]);
+	fs::write(dir.join("source/inner/file.txt"), b"data").unwrap();
+	dir
+}
+
+fn is_into_itself(result: rsfiles_core::Result<()>) -> bool {
+	matches!(result, Err(Error::IntoItself(_)))
+}
+
+fn entries_below(path: &Path) -> usize {
+	fs::read_dir(path).unwrap().flatten()
+		.map(|entry| 1 + if entry.file_type().unwrap().is_dir() { entries_below(&entry.path()) } else { 0 })
+		.sum()
+}
+
+#[test]
+fn refuses_the_folder_itself_and_its_subfolders() {
+	let dir = tree("guard-subtree");
+	let source = dir.join("source");
+
+	assert!(is_into_itself(check_destination(&source, &source)));
+	assert!(is_into_itself(check_destination(&source, &source.join("inner"))));
+	assert!(is_into_itself(check_destination(&source, &source.join("inner/deep"))));
+	// Through a path that is not written the canonical way
+	assert!(is_into_itself(check_destination(&source, &source.join("inner/../inner/./deep"))));
+	// A folder that is only about to be created
+	assert!(is_into_itself(check_destination(&source, &source.join("inner/new/newer"))));
+}
+
+#[test]
+fn allows_parents_and_siblings() {
+	let dir = tree("guard-siblings");
+	let source = dir.join("source");
+	// Shares the beginning of the name but is not below it
+	fs::create_dir(dir.join("source2")).unwrap();
+
+	assert!(check_destination(&source, &dir).is_ok());
+	assert!(check_destination(&source, &dir.join("source2")).is_ok());
+	// Files have no subtree
+	assert!(check_destination(&source.join("inner/file.txt"), &source.join("inner")).is_ok());
+}
+
+#[cfg(unix)]
+#[test]
+fn sees_through_symlinked_aliases() {
+	use std::os::unix::fs::symlink;
+
+	let dir = tree("guard-symlinks");
+	let source = dir.join("source");
+	// Another name for the source folder and a link elsewhere that points into it
+	symlink(&source, dir.join("alias")).unwrap();
+	fs::create_dir(dir.join("elsewhere")).unwrap();
+	symlink(source.join("inner/deep"), dir.join("elsewhere/shortcut")).unwrap();
+
+	assert!(is_into_itself(check_destination(&source, &dir.join("alias"))));
+	assert!(is_into_itself(check_destination(&source, &dir.join("alias/inner"))));
+	assert!(is_into_itself(check_destination(&source, &dir.join("elsewhere/shortcut"))));
+	assert!(is_into_itself(check_destination(&source, &dir.join("alias/new"))));
+	assert!(is_into_itself(check_destination(&source, &dir.join("elsewhere/shortcut/new/newer"))));
+	assert!(check_destination(&source, &dir.join("elsewhere/new")).is_ok());
+	// The link itself is moved or copied as a link, that may go anywhere
+	assert!(check_destination(&dir.join("alias"), &source).is_ok());
+}
+
+/// Bind mounts need root, the test passes without checking anything when mounting fails
+#[cfg(target_os = "linux")]
+#[test]
+fn sees_through_bind_mounts() {
+	use std::process::Command;
+
+	struct Mount(PathBuf);
+	impl Drop for Mount {
+		fn drop(&mut self) {
+			let _ = Command::new("umount").arg(&self.0).status();
+		}
+	}
+
+	let dir = tree("guard-bind");
+	let source = dir.join("source");
+	let mount_point = dir.join("mounted");
+	fs::create_dir(&mount_point).unwrap();
+
+	let mounted = Command::new("mount")
+		.arg("--bind")
+		.arg(source.join("inner"))
+		.arg(&mount_point)
+		.stderr(std::process::Stdio::null())
+		.status()
+		.is_ok_and(|status| status.success());
+	if mounted {
+		let _mount = Mount(mount_point.clone());
+		assert!(mount_point.join("file.txt").exists());
+		assert!(is_into_itself(check_destination(&source.join("inner"), &mount_point)));
+		assert!(is_into_itself(check_destination(&source, &mount_point.join("deep"))));
+	}
+}
+
+#[test]
+fn copy_and_move_leave_everything_as_it_was() {
+	let dir = tree("guard-ops");
+	let source = dir.join("source");
+	let before = entries_below(&dir);
+
+	assert!(is_into_itself(copy_path(&source, &source.join("inner/source"))));
+	assert!(is_into_itself(move_path(&source, &source.join("inner/deep/source"))));
+
+	let job = Job::Copy { sources: vec![source.clone()], dest: source.join("inner"), conflicts: ConflictPolicy::new(), options: CopyOptions::default() };
+	let outcome = run_job(&job, &JobControl::new(), |_| {});
+	assert_eq!(outcome.failures.len(), 1);
+	assert_eq!(outcome.failures[0].path, source);
+
+	let job = Job::Move { sources: vec![source.clone()], dest: source.join("inner/deep"), conflicts: ConflictPolicy::new(), options: CopyOptions::default() };
+	assert_eq!(run_job(&job, &JobControl::new(), |_| {}).failures.len(), 1);
+
+	assert_eq!(entries_below(&dir), before);
+}
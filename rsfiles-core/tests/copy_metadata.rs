@@ -0,0 +1,244 @@
+use rsfiles_core::{copy_entry, copy_path, CopyOptions};
+use std::{
+	fs::{self, File, FileTimes},
+	path::PathBuf,
+	time::{Duration, SystemTime},
+};
+
+mod common;
+use common::Scratch;
+
+fn long_ago() -> SystemTime {
+	SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000)
+}
+
+fn set_modified(path: &std::path::Path, time: SystemTime) {
+	File::open(path).unwrap().set_times(FileTimes::new().set_modified(time)).unwrap();
+}
+
+#[test]
+fn keeps_times_of_files_and_folders() {
+	let dir = Scratch::new("copy-times", &["source"]);
+	let source = dir.join("source");
+	fs::create_dir(source.join("inner")).unwrap();
+	fs::write(source.join("inner/file.txt"), b"data").unwrap();
+	set_modified(&source.join("inner/file.txt"), long_ago());
+	// After the file, writing it changed the folder
+	set_modified(&source.join("inner"), long_ago());
+	set_modified(&source, long_ago() + Duration::from_secs(60));
+
+	let target = dir.join("target");
+	assert_eq!(copy_entry(&source, &target, &CopyOptions::default()), vec![]);
+
+	let modified = |path: PathBuf| fs::metadata(path).unwrap().modified().unwrap();
+	assert_eq!(modified(target.join("inner/file.txt")), long_ago());
+	assert_eq!(modified(target.join("inner")), long_ago());
+	assert_eq!(modified(target.clone()), long_ago() + Duration::from_secs(60));
+	assert_eq!(fs::read(target.join("inner/file.txt")).unwrap(), b"data");
+}
+
+#[test]
+fn times_are_left_alone_when_not_wanted() {
+	let dir = Scratch::new("copy-no-times", &["source"]);
+	let file = dir.join("source/file.txt");
+	fs::write(&file, b"data").unwrap();
+	set_modified(&file, long_ago());
+
+	let options = CopyOptions { times: false, ..CopyOptions::default() };
+	assert_eq!(copy_entry(&file, &dir.join("copy.txt"), &options), vec![]);
+	assert_ne!(fs::metadata(dir.join("copy.txt")).unwrap().modified().unwrap(), long_ago());
+}
+
+/// Times are set by the path, a file that can not be read gets them as well
+#[cfg(unix)]
+#[test]
+fn sets_times_of_a_file_that_can_not_be_read() {
+	use rsfiles_core::platform;
+	use std::os::unix::fs::PermissionsExt;
+
+	let dir = Scratch::new("times-write-only", &[]);
+	let file = dir.join("write-only.txt");
+	fs::write(&file, b"data").unwrap();
+	fs::set_permissions(&file, fs::Permissions::from_mode(0o200)).unwrap();
+	let accessed = fs::metadata(&file).unwrap().accessed().unwrap();
+
+	platform::set_file_times(&file, None, Some(long_ago())).unwrap();
+	let metadata = fs::metadata(&file).unwrap();
+	assert_eq!(metadata.modified().unwrap(), long_ago());
+	// Not given, so left as it was
+	assert_eq!(metadata.accessed().unwrap(), accessed);
+
+	let before_1970 = SystemTime::UNIX_EPOCH - Duration::from_millis(1500);
+	platform::set_file_times(&file, Some(before_1970), None).unwrap();
+	assert_eq!(fs::metadata(&file).unwrap().accessed().unwrap(), before_1970);
+}
+
+#[cfg(unix)]
+#[test]
+fn keeps_modes_of_files_and_folders() {
+	use std::os::unix::fs::PermissionsExt;
+
+	let dir = Scratch::new("copy-modes", &["source"]);
+	let source = dir.join("source");
+	fs::create_dir(source.join("locked")).unwrap();
+	fs::write(source.join("locked/script.sh"), b"#!/bin/sh\n").unwrap();
+	fs::write(source.join("private.txt"), b"secret").unwrap();
+	fs::set_permissions(source.join("locked/script.sh"), fs::Permissions::from_mode(0o751)).unwrap();
+	fs::set_permissions(source.join("private.txt"), fs::Permissions::from_mode(0o600)).unwrap();
+	// Read-only folder, its file still has to get in before the mode is set
+	fs::set_permissions(source.join("locked"), fs::Permissions::from_mode(0o555)).unwrap();
+
+	let target = dir.join("target");
+	assert_eq!(copy_entry(&source, &target, &CopyOptions::default()), vec![]);
+
+	let mode = |path: PathBuf| fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+	assert_eq!(mode(target.join("locked/script.sh")), 0o751);
+	assert_eq!(mode(target.join("private.txt")), 0o600);
+	assert_eq!(mode(target.join("locked")), 0o555);
+
+	for folder in [source.join("locked"), target.join("locked")] {
+		fs::set_permissions(folder, fs::Permissions::from_mode(0o755)).unwrap();
+	}
+}
+
+#[cfg(unix)]
+#[test]
+fn keeps_links_as_links() {
+	use std::os::unix::fs::symlink;
+
+	let dir = Scratch::new("copy-links", &["source"]);
+	let source = dir.join("source");
+	fs::write(source.join("file.txt"), b"data").unwrap();
+	fs::create_dir(source.join("folder")).unwrap();
+	symlink("file.txt", source.join("relative")).unwrap();
+	symlink("folder", source.join("folder-link")).unwrap();
+	symlink("nowhere", source.join("dangling")).unwrap();
+
+	let target = dir.join("target");
+	assert_eq!(copy_entry(&source, &target, &CopyOptions::default()), vec![]);
+
+	for (link, points_to) in [("relative", "file.txt"), ("folder-link", "folder"), ("dangling", "nowhere")] {
+		assert!(fs::symlink_metadata(target.join(link)).unwrap().is_symlink(), "{}", link);
+		assert_eq!(fs::read_link(target.join(link)).unwrap(), PathBuf::from(points_to));
+	}
+	// A single link copies as a link too
+	copy_path(&source.join("relative"), &dir.join("single")).unwrap();
+	assert_eq!(fs::read_link(dir.join("single")).unwrap(), PathBuf::from("file.txt"));
+}
+
+#[cfg(unix)]
+#[test]
+fn follows_links_when_asked() {
+	use std::os::unix::fs::symlink;
+
+	let dir = Scratch::new("copy-follow", &["source"]);
+	let source = dir.join("source");
+	fs::create_dir(dir.join("outside")).unwrap();
+	fs::write(dir.join("outside/file.txt"), b"data").unwrap();
+	symlink(dir.join("outside"), source.join("folder-link")).unwrap();
+	symlink(dir.join("outside/file.txt"), source.join("file-link")).unwrap();
+
+	let target = dir.join("target");
+	let options = CopyOptions { symlinks: false, ..CopyOptions::default() };
+	assert_eq!(copy_entry(&source, &target, &options), vec![]);
+
+	assert!(fs::symlink_metadata(target.join("folder-link")).unwrap().is_dir());
+	assert_eq!(fs::read(target.join("folder-link/file.txt")).unwrap(), b"data");
+	assert!(fs::symlink_metadata(target.join("file-link")).unwrap().is_file());
+}
+
+/// A link given as the thing to copy is copied as a link, nothing is written through it
+#[cfg(unix)]
+#[test]
+fn copies_a_link_to_a_folder_as_a_link() {
+	use std::os::unix::fs::symlink;
+
+	let dir = Scratch::new("copy-root-link", &["source"]);
+	fs::write(dir.join("source/file.txt"), b"data").unwrap();
+	symlink(dir.join("source"), dir.join("link")).unwrap();
+
+	assert_eq!(copy_entry(&dir.join("link"), &dir.join("copy"), &CopyOptions::default()), vec![]);
+	assert_eq!(fs::read_link(dir.join("copy")).unwrap(), dir.join("source"));
+	copy_path(&dir.join("link"), &dir.join("single")).unwrap();
+	assert_eq!(fs::read_link(dir.join("single")).unwrap(), dir.join("source"));
+	assert_eq!(fs::read(dir.join("source/file.txt")).unwrap(), b"data");
+
+	// Followed when links are not kept
+	let options = CopyOptions { symlinks: false, ..CopyOptions::default() };
+	assert_eq!(copy_entry(&dir.join("link"), &dir.join("followed"), &options), vec![]);
+	assert!(fs::symlink_metadata(dir.join("followed")).unwrap().is_dir());
+	assert_eq!(fs::read(dir.join("followed/file.txt")).unwrap(), b"data");
+	assert_eq!(fs::read(dir.join("source/file.txt")).unwrap(), b"data");
+}
+
+/// Needs a file system with user extended attributes, passes without checking otherwise
+#[cfg(target_os = "linux")]
+#[test]
+fn keeps_extended_attributes() {
+	use rsfiles_core::platform;
+	use std::ffi::OsString;
+
+	let dir = Scratch::new("copy-xattrs", &["source"]);
+	let file = dir.join("source/tagged.txt");
+	fs::write(&file, b"data").unwrap();
+	let attribute = (OsString::from("user.rsfiles.tag"), b"blue".to_vec());
+	if platform::write_xattrs(&file, std::slice::from_ref(&attribute)).is_ok() {
+		assert_eq!(copy_entry(&file, &dir.join("copy.txt"), &CopyOptions::default()), vec![]);
+		assert!(platform::read_xattrs(&dir.join("copy.txt")).unwrap().contains(&attribute));
+
+		let options = CopyOptions { xattrs: false, ..CopyOptions::default() };
+		assert_eq!(copy_entry(&file, &dir.join("plain.txt"), &options), vec![]);
+		assert!(!platform::read_xattrs(&dir.join("plain.txt")).unwrap().contains(&attribute));
+	}
+}
+
+#[cfg(unix)]
+#[test]
+fn keeps_holes_in_sparse_files() {
+	use std::os::unix::fs::MetadataExt;
+
+	let dir = Scratch::new("copy-sparse", &["source"]);
+	let file = dir.join("source/sparse.bin");
+	// 64 MiB with a few bytes at the start and the end, the rest is a hole
+	let length = 64 * 1024 * 1024;
+	fs::write(&file, b"start").unwrap();
+	let handle = fs::OpenOptions::new().write(true).open(&file).unwrap();
+	handle.set_len(length).unwrap();
+	std::os::unix::fs::FileExt::write_at(&handle, b"end", length - 3).unwrap();
+	drop(handle);
+
+	let copy = dir.join("copy.bin");
+	assert_eq!(copy_entry(&file, &copy, &CopyOptions::default()), vec![]);
+	let (original, copied) = (fs::metadata(&file).unwrap(), fs::metadata(&copy).unwrap());
+	assert_eq!(copied.len(), length);
+	assert_eq!(fs::read(&copy).unwrap(), fs::read(&file).unwrap());
+	// Only if the file system made a hole in the first place
+	if original.blocks() * 512 < length {
+		assert!(copied.blocks() * 512 < length);
+	}
+}
+
+/// A named pipe would block a copy that reads it, it is reported and skipped instead
+#[cfg(unix)]
+#[test]
+fn reports_named_pipes_instead_of_blocking() {
+	use std::process::Command;
+
+	let dir = Scratch::new("copy-fifo", &["source"]);
+	let source = dir.join("source");
+	fs::write(source.join("file.txt"), b"data").unwrap();
+	let made = Command::new("mkfifo")
+		.arg(source.join("pipe"))
+		.status()
+		.is_ok_and(|status| status.success());
+	if made {
+		let target = dir.join("target");
+		let failures = copy_entry(&source, &target, &CopyOptions::default());
+		assert_eq!(failures.len(), 1);
+		assert_eq!(failures[0].path, source.join("pipe"));
+		assert!(failures[0].message.contains("named pipe"));
+		// Everything else made it
+		assert_eq!(fs::read(target.join("file.txt")).unwrap(), b"data");
+		assert!(fs::symlink_metadata(target.join("pipe")).is_err());
+	}
+}
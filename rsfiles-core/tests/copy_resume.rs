@@ -0,0 +1,217 @@
+use rsfiles_core::{run_job_journaled, ConflictAction, ConflictPolicy, CopyJournal, CopyOptions, Job, JobControl, JobProgress};
+use std::{
+	fs::{self, File, FileTimes},
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime},
+};
+
+mod common;
+use common::Scratch;
+
+/// Contents without runs of zeros, so nothing is left as a hole
+fn data(length: usize) -> Vec<u8> {
+	(0..length).map(|i| (i % 251) as u8 + 1).collect()
+}
+
+fn copy_job(dir: &Path, options: CopyOptions) -> Job {
+	Job::Copy { sources: vec![dir.join("source")], dest: dir.join("target"), conflicts: ConflictPolicy::new(), options }
+}
+
+/// Runs the job of `journal` and cancels it at the first progress report `stop` agrees to,
+/// with a report after every file and chunk
+fn run_until(journal: CopyJournal, stop: impl Fn(&JobProgress) -> bool) -> rsfiles_core::JobOutcome {
+	let control = JobControl::new().with_report_interval(Duration::ZERO);
+	run_job_journaled(journal, &control, |progress: &JobProgress| {
+		if stop(progress) {
+			control.cancel();
+		}
+	})
+}
+
+#[test]
+fn finished_files_are_skipped_unless_the_original_changed() {
+	let dir = Scratch::new("resume-skip", &["source", "target"]);
+	let source = dir.join("source");
+	fs::write(source.join("kept.txt"), b"kept").unwrap();
+	fs::write(source.join("changed.txt"), b"before").unwrap();
+	let large = data(3 * 1024 * 1024);
+	fs::write(source.join("large.bin"), &large).unwrap();
+
+	// Cancelled once every file is copied, before the job could count itself finished.
+	// Small files come back from the workers one by one, with a report after each.
+	let options = CopyOptions { workers: 2, ..CopyOptions::default() };
+	let journal_path = dir.join("copy.journal");
+	let journal = CopyJournal::create(&journal_path, &copy_job(&dir, options)).unwrap();
+	let outcome = run_until(journal, |progress| progress.files_total > 0 && progress.files_done == progress.files_total);
+	assert!(outcome.cancelled);
+	assert!(journal_path.exists());
+	// Put in place under their own names once complete, like larger files
+	assert!(fs::symlink_metadata(dir.join("target/source/.kept.txt.rsfiles-part")).is_err());
+
+	// Same sizes, so only the journal can tell which copies to keep
+	let copies = dir.join("target/source");
+	fs::write(copies.join("kept.txt"), b"KEPT").unwrap();
+	fs::write(copies.join("changed.txt"), b"BEFORE").unwrap();
+	fs::write(source.join("changed.txt"), b"after!").unwrap();
+	File::options().write(true).open(source.join("changed.txt")).unwrap()
+		.set_times(FileTimes::new().set_modified(SystemTime::now() + Duration::from_secs(60)))
+		.unwrap();
+
+	let journal = CopyJournal::load(&journal_path).unwrap();
+	let outcome = run_job_journaled(journal, &JobControl::new(), |_| {});
+	assert_eq!(outcome.failures, vec![]);
+	assert!(!outcome.cancelled);
+	assert_eq!(outcome.progress.files_done, 3);
+	assert_eq!(outcome.progress.bytes_done, outcome.progress.bytes_total);
+
+	assert_eq!(fs::read(copies.join("kept.txt")).unwrap(), b"KEPT");
+	assert_eq!(fs::read(copies.join("changed.txt")).unwrap(), b"after!");
+	assert_eq!(fs::read(copies.join("large.bin")).unwrap(), large);
+	// A job that ran to the end does not need its journal anymore
+	assert!(!journal_path.exists());
+}
+
+#[test]
+fn partly_copied_file_goes_on_from_where_it_stopped() {
+	let dir = Scratch::new("resume-partial", &["source", "target"]);
+	let large = data(8 * 1024 * 1024);
+	fs::write(dir.join("source/large.bin"), &large).unwrap();
+
+	let options = CopyOptions { kernel_copy: false, ..CopyOptions::default() };
+	let journal_path = dir.join("copy.journal");
+	let journal = CopyJournal::create(&journal_path, &copy_job(&dir, options)).unwrap();
+
+	let outcome = run_until(journal, |progress| progress.bytes_done > 0);
+	assert!(outcome.cancelled);
+
+	// Not under its own name before it is complete
+	let copy = dir.join("target/source/large.bin");
+	let part = dir.join("target/source/.large.bin.rsfiles-part");
+	assert!(fs::symlink_metadata(&copy).is_err());
+	let copied = fs::metadata(&part).unwrap().len();
+	assert!(copied > 0 && copied < large.len() as u64, "{} bytes copied", copied);
+	// Marks the part that is there, going on from it keeps the mark
+	let mut partial = fs::read(&part).unwrap();
+	partial[0] = 0;
+	fs::write(&part, &partial).unwrap();
+
+	let journal = CopyJournal::load(&journal_path).unwrap();
+	let outcome = run_job_journaled(journal, &JobControl::new(), |_| {});
+	assert_eq!(outcome.failures, vec![]);
+	assert_eq!(outcome.progress.bytes_done, large.len() as u64);
+
+	let resumed = fs::read(&copy).unwrap();
+	assert_eq!(resumed[0], 0);
+	assert_eq!(resumed[1..], large[1..]);
+	assert!(fs::symlink_metadata(&part).is_err());
+}
+
+/// A job that is not going on takes its partly copied files with it
+#[test]
+fn discarded_job_removes_its_partial_files() {
+	let dir = Scratch::new("resume-discard", &["source", "target"]);
+	fs::write(dir.join("source/large.bin"), data(8 * 1024 * 1024)).unwrap();
+
+	let options = CopyOptions { kernel_copy: false, ..CopyOptions::default() };
+	let journal_path = dir.join("copy.journal");
+	let journal = CopyJournal::create(&journal_path, &copy_job(&dir, options)).unwrap();
+	let outcome = run_until(journal, |progress| progress.bytes_done > 0);
+	assert!(outcome.cancelled);
+	let part = dir.join("target/source/.large.bin.rsfiles-part");
+	assert!(part.exists());
+	// What a crash left of a small file, the journal never heard of it
+	let cut_short = dir.join("target/source/.small.txt.rsfiles-part");
+	fs::write(&cut_short, b"sm").unwrap();
+
+	CopyJournal::load(&journal_path).unwrap().discard().unwrap();
+	assert!(!journal_path.exists());
+	assert!(fs::symlink_metadata(&part).is_err());
+	assert!(fs::symlink_metadata(&cut_short).is_err());
+	assert!(fs::symlink_metadata(dir.join("target/source/large.bin")).is_err());
+}
+
+/// Cancels a copy that replaces the folder `target/source` halfway through its large file
+fn cancel_replace(dir: &Path) -> PathBuf {
+	fs::write(dir.join("source/large.bin"), data(8 * 1024 * 1024)).unwrap();
+	fs::create_dir(dir.join("target/source")).unwrap();
+	fs::write(dir.join("target/source/old.txt"), b"old").unwrap();
+
+	let mut conflicts = ConflictPolicy::new();
+	conflicts.decide(dir.join("source"), ConflictAction::Replace);
+	let options = CopyOptions { kernel_copy: false, ..CopyOptions::default() };
+	let job = Job::Copy { sources: vec![dir.join("source")], dest: dir.join("target"), conflicts, options };
+	let journal_path = dir.join("copy.journal");
+	let outcome = run_until(CopyJournal::create(&journal_path, &job).unwrap(), |progress| progress.bytes_done > 0);
+	assert!(outcome.cancelled);
+	// The old folder stays aside, what was copied of the new one stays in its place
+	assert_eq!(fs::read(dir.join("target/.source (1).replaced/old.txt")).unwrap(), b"old");
+	assert!(dir.join("target/source/.large.bin.rsfiles-part").exists());
+	journal_path
+}
+
+#[test]
+fn cancelled_replace_goes_on_once_resumed() {
+	let dir = Scratch::new("resume-replace", &["source", "target"]);
+	let journal_path = cancel_replace(&dir);
+
+	let outcome = run_job_journaled(CopyJournal::load(&journal_path).unwrap(), &JobControl::new(), |_| {});
+	assert_eq!(outcome.failures, vec![]);
+	assert_eq!(outcome.progress.bytes_done, 8 * 1024 * 1024);
+	assert_eq!(fs::read(dir.join("target/source/large.bin")).unwrap(), data(8 * 1024 * 1024));
+	assert!(fs::symlink_metadata(dir.join("target/source/old.txt")).is_err());
+	assert!(fs::symlink_metadata(dir.join("target/.source (1).replaced")).is_err());
+}
+
+#[test]
+fn discarded_replace_puts_the_old_entry_back() {
+	let dir = Scratch::new("resume-replace-discard", &["source", "target"]);
+	let journal_path = cancel_replace(&dir);
+
+	CopyJournal::load(&journal_path).unwrap().discard().unwrap();
+	assert_eq!(fs::read(dir.join("target/source/old.txt")).unwrap(), b"old");
+	assert!(fs::symlink_metadata(dir.join("target/source/.large.bin.rsfiles-part")).is_err());
+	assert!(fs::symlink_metadata(dir.join("target/.source (1).replaced")).is_err());
+}
+
+/// Names picked for taken ones are part of the plan, the journal does not need the decisions
+#[test]
+fn resumed_job_keeps_its_plan() {
+	let dir = Scratch::new("resume-plan", &["source", "target"]);
+	fs::write(dir.join("source/file.txt"), b"new").unwrap();
+	fs::write(dir.join("target/file.txt"), b"old").unwrap();
+
+	let mut conflicts = ConflictPolicy::new();
+	conflicts.decide(dir.join("source/file.txt"), ConflictAction::KeepBoth);
+	let job = Job::Copy { sources: vec![dir.join("source/file.txt")], dest: dir.join("target"), conflicts, options: CopyOptions::default() };
+	let journal_path = dir.join("copy.journal");
+	let control = JobControl::new();
+	control.cancel();
+	let outcome = run_job_journaled(CopyJournal::create(&journal_path, &job).unwrap(), &control, |_| {});
+	assert!(outcome.cancelled);
+	assert!(fs::symlink_metadata(dir.join("target/file (1).txt")).is_err());
+
+	let outcome = run_job_journaled(CopyJournal::load(&journal_path).unwrap(), &JobControl::new(), |_| {});
+	assert_eq!(outcome.failures, vec![]);
+	assert_eq!(fs::read(dir.join("target/file.txt")).unwrap(), b"old");
+	assert_eq!(fs::read(dir.join("target/file (1).txt")).unwrap(), b"new");
+}
+
+/// A line a crash cut off halfway does not count, "finished 0" could be the start of "finished 0X"
+#[test]
+fn line_cut_off_at_the_end_is_ignored() {
+	let dir = Scratch::new("resume-torn", &["source", "target"]);
+	fs::write(dir.join("source/file.txt"), b"data").unwrap();
+
+	let journal_path = dir.join("copy.journal");
+	let control = JobControl::new();
+	control.cancel();
+	let journal = CopyJournal::create(&journal_path, &copy_job(&dir, CopyOptions::default())).unwrap();
+	assert!(run_job_journaled(journal, &control, |_| {}).cancelled);
+	let mut contents = fs::read(&journal_path).unwrap();
+	contents.extend_from_slice(b"finished\t0");
+	fs::write(&journal_path, contents).unwrap();
+
+	let outcome = run_job_journaled(CopyJournal::load(&journal_path).unwrap(), &JobControl::new(), |_| {});
+	assert_eq!(outcome.failures, vec![]);
+	assert_eq!(fs::read(dir.join("target/source/file.txt")).unwrap(), b"data");
+}
@@ -0,0 +1,32 @@
+use rsfiles_core::{run_job, ConflictPolicy, CopyOptions, Job, JobControl};
+use std::fs;
+
+mod common;
+use common::Scratch;
+
+#[test]
+fn verified_copy_of_small_and_large_files() {
+	let dir = Scratch::new("verify-copy", &["source", "target"]);
+	let source = dir.join("source");
+	for index in 0..40 {
+		fs::write(source.join(format!("{}.txt", index)), format!("small file {}", index)).unwrap();
+	}
+	let large: Vec<u8> = (0..2 * 1024 * 1024 + 7).map(|i: u32| (i % 253) as u8).collect();
+	fs::write(source.join("large.bin"), &large).unwrap();
+
+	for workers in [1, 4] {
+		let options = CopyOptions { verify: true, workers, ..CopyOptions::default() };
+		let dest = dir.join(format!("target/{}", workers));
+		fs::create_dir(&dest).unwrap();
+		let job = Job::Copy { sources: vec![source.clone()], dest: dest.clone(), conflicts: ConflictPolicy::new(), options };
+		let outcome = run_job(&job, &JobControl::new(), |_| {});
+
+		assert_eq!(outcome.failures, vec![], "{} workers", workers);
+		assert_eq!(outcome.progress.files_done, 41);
+		// Reading files back for the check does not count as copying them
+		assert_eq!(outcome.progress.bytes_done, outcome.progress.bytes_total);
+		assert_eq!(fs::read(dest.join("source/large.bin")).unwrap(), large);
+		assert_eq!(fs::read(dest.join("source/7.txt")).unwrap(), b"small file 7");
+	}
+}
+
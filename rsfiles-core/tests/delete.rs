@@ -0,0 +1,25 @@
+use rsfiles_core::{run_job, Job, JobControl};
+use std::fs;
+
+mod common;
+use common::Scratch;
+
+/// Only the link goes, the folder it points to and everything in it stay
+#[cfg(unix)]
+#[test]
+fn deletes_a_link_to_a_folder_as_a_link() {
+	use std::os::unix::fs::symlink;
+
+	let dir = Scratch::new("delete-link", &["source"]);
+	fs::create_dir(dir.join("source/inner")).unwrap();
+	fs::write(dir.join("source/file.txt"), b"data").unwrap();
+	fs::write(dir.join("source/inner/deep.txt"), b"deep").unwrap();
+	symlink(dir.join("source"), dir.join("link")).unwrap();
+
+	let outcome = run_job(&Job::Delete { paths: vec![dir.join("link")] }, &JobControl::new(), |_| {});
+	assert_eq!(outcome.failures, vec![]);
+	assert_eq!(outcome.progress.files_total, 1);
+	assert!(fs::symlink_metadata(dir.join("link")).is_err());
+	assert_eq!(fs::read(dir.join("source/file.txt")).unwrap(), b"data");
+	assert_eq!(fs::read(dir.join("source/inner/deep.txt")).unwrap(), b"deep");
+}
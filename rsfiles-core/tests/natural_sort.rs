@@ -0,0 +1,109 @@
+use rsfiles_core::{load_directory_contents, merge_sorted, natural_cmp, sort_directory_contents, Collation, FileEntry, SortColumn, SortOrder};
+use std::{cmp::Ordering, fs};
+
+mod common;
+use common::Scratch;
+
+fn sorted(names: &[&str]) -> Vec<String> {
+	let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+	names.sort_by(|a, b| natural_cmp(a, b));
+	names
+}
+
+/// Scratch directory holding an empty file per name
+fn directory_with(test: &str, names: &[&str]) -> Scratch {
+	let dir = Scratch::new(test, &[]);
+	for name in names {
+		fs::write(dir.join(name), b"").unwrap();
+	}
+	dir
+}
+
+#[test]
+fn digit_runs_compare_as_numbers() {
+	assert_eq!(
+		sorted(&["file10.txt", "file2.txt", "file1.txt", "file20.txt", "file3.txt"]),
+		["file1.txt", "file2.txt", "file3.txt", "file10.txt", "file20.txt"],
+	);
+	assert_eq!(
+		sorted(&["IMG_1000.jpg", "IMG_999.jpg", "IMG_99a.jpg", "IMG_99.jpg"]),
+		["IMG_99.jpg", "IMG_99a.jpg", "IMG_999.jpg", "IMG_1000.jpg"],
+	);
+	assert_eq!(sorted(&["v1.10.0", "v1.9.2", "v1.9.10"]), ["v1.9.2", "v1.9.10", "v1.10.0"]);
+}
+
+#[test]
+fn leading_zeros_do_not_change_the_value() {
+	assert_eq!(sorted(&["track 010", "track 9", "track 0011"]), ["track 9", "track 010", "track 0011"]);
+	// Equal numbers still get a consistent order
+	assert_ne!(natural_cmp("a01", "a1"), Ordering::Equal);
+	assert_eq!(natural_cmp("a01", "a1"), natural_cmp("a1", "a01").reverse());
+}
+
+/// A digit run sits where its first digit would, after `-` and before `~` or `€`
+#[test]
+fn numbers_keep_their_place_among_other_characters() {
+	assert_eq!(sorted(&["a€", "a~", "a12", "a-", "a9z", "a\u{1F600}"]), ["a-", "a9z", "a12", "a~", "a€", "a\u{1F600}"]);
+}
+
+#[test]
+fn case_and_accents_only_break_ties() {
+	assert_eq!(sorted(&["b", "B", "a", "A"]), ["A", "a", "B", "b"]);
+	assert_eq!(sorted(&["éclair", "eclair", "ecole", "Eden", "zebra"]), ["eclair", "éclair", "ecole", "Eden", "zebra"]);
+	assert_eq!(sorted(&["Ärger", "Zoo", "Apfel", "arm"]), ["Apfel", "Ärger", "arm", "Zoo"]);
+	// Precomposed and decomposed forms of the same name sit next to each other
+	let names = sorted(&["cafez", "cafe\u{301}", "cafea", "caf\u{e9}"]);
+	assert_eq!(names[2..], ["cafea", "cafez"]);
+}
+
+#[test]
+fn dotfiles_come_before_names_and_numbers() {
+	assert_eq!(
+		sorted(&["readme", ".gitignore", "10-setup", ".cargo", "2-intro", "_build"]),
+		[".cargo", ".gitignore", "2-intro", "10-setup", "_build", "readme"],
+	);
+}
+
+#[test]
+fn directory_listing_uses_natural_order() {
+	let dir = directory_with("natural", &["frame10.png", "frame2.png", "Frame1.png", ".config", "échelle.png"]);
+	fs::create_dir(dir.join("shots 10")).unwrap();
+	fs::create_dir(dir.join("shots 9")).unwrap();
+
+	let mut files = load_directory_contents(&dir.to_path_buf()).unwrap();
+	let order = SortOrder { column: SortColumn::Name, ..SortOrder::default() };
+	sort_directory_contents(&mut files, &order);
+	let names: Vec<&str> = files.iter().map(|file| file.display_name()).collect();
+	assert_eq!(names, ["shots 9", "shots 10", ".config", "échelle.png", "Frame1.png", "frame2.png", "frame10.png"]);
+}
+
+#[test]
+fn byte_order_can_be_selected() {
+	let dir = directory_with("bytes", &["file10", "file2", "File3", ".hidden", "élan", "zeta"]);
+
+	let mut files = load_directory_contents(&dir.to_path_buf()).unwrap();
+	let order = SortOrder { column: SortColumn::Name, collation: Collation::Bytes, ..SortOrder::default() };
+	sort_directory_contents(&mut files, &order);
+	let names: Vec<&str> = files.iter().map(|file| file.display_name()).collect();
+	assert_eq!(names, [".hidden", "File3", "file10", "file2", "zeta", "élan"]);
+}
+
+/// Batches merged in as they are loaded end up where sorting them all at once puts them
+#[test]
+fn merged_batches_match_one_sort() {
+	let dir = directory_with("merge", &["b10.txt", "b9.txt", "A.md", "a.md", "c.txt", "b1.rs", ".env", "z.md"]);
+	fs::create_dir(dir.join("docs 2")).unwrap();
+	fs::create_dir(dir.join("docs 10")).unwrap();
+	let files = load_directory_contents(&dir.to_path_buf()).unwrap();
+
+	for order in [SortOrder::default(), SortOrder { column: SortColumn::Name, descending: true, ..SortOrder::default() }] {
+		let mut sorted = files.clone();
+		sort_directory_contents(&mut sorted, &order);
+		let mut merged = Vec::new();
+		for batch in files.chunks(3) {
+			merge_sorted(&mut merged, batch.to_vec(), &order);
+		}
+		let names = |files: &[FileEntry]| files.iter().map(|file| file.display_name().to_string()).collect::<Vec<_>>();
+		assert_eq!(names(&merged), names(&sorted));
+	}
+}
@@ -0,0 +1,61 @@
+use rsfiles_core::ShortcutInfo;
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+#[test]
+fn parses_local_shortcut() {
+	let info = ShortcutInfo::from_path(&fixture("local.lnk")).unwrap();
+
+	assert_eq!(info.local_base_path.as_deref(), Some(r"C:\Windows\System32\notepad.exe"));
+	assert_eq!(info.network_path, None);
+	assert_eq!(info.name.as_deref(), Some("Text editor"));
+	assert_eq!(info.relative_path.as_deref(), Some(r"..\..\Windows\System32\notepad.exe"));
+	assert_eq!(info.working_dir.as_deref(), Some(r"C:\Windows\System32"));
+	assert_eq!(info.arguments.as_deref(), Some("/A readme.txt"));
+	assert_eq!(info.icon_location.as_deref(), Some(r"%SystemRoot%\System32\shell32.dll"));
+	assert_eq!(info.target().as_deref(), Some(r"C:\Windows\System32\notepad.exe"));
+}
+
+#[test]
+fn parses_network_shortcut() {
+	let info = ShortcutInfo::from_path(&fixture("network.lnk")).unwrap();
+
+	assert_eq!(info.local_base_path, None);
+	assert_eq!(info.network_path.as_deref(), Some(r"\\server\share"));
+	assert_eq!(info.device_name.as_deref(), Some("Z:"));
+	assert_eq!(info.common_path_suffix.as_deref(), Some(r"docs\report.docx"));
+	assert_eq!(info.working_dir.as_deref(), Some(r"Z:\docs"));
+	assert_eq!(info.target().as_deref(), Some(r"\\server\share\docs\report.docx"));
+}
+
+#[test]
+fn prefers_unicode_local_path() {
+	let info = ShortcutInfo::from_path(&fixture("unicode_path.lnk")).unwrap();
+
+	assert_eq!(info.local_base_path.as_deref(), Some(r"C:\Users\Zoë\Desktop\日本"));
+	assert_eq!(info.common_path_suffix, None);
+}
+
+#[test]
+fn resolves_through_relative_path() {
+	let lnk = fixture("relative.lnk");
+	let info = ShortcutInfo::from_path(&lnk).unwrap();
+
+	assert_eq!(info.target(), None);
+	let target = info.resolve(&lnk).unwrap();
+	assert!(target.is_dir());
+	assert_eq!(target.canonicalize().unwrap(), fixture("").canonicalize().unwrap());
+}
+
+#[test]
+fn rejects_invalid_data() {
+	let data = std::fs::read(fixture("local.lnk")).unwrap();
+
+	assert!(ShortcutInfo::parse(b"not a shortcut").is_err());
+	for len in [10, 0x60, 0x100, data.len() - 10] {
+		assert!(ShortcutInfo::parse(&data[..len]).is_err(), "truncated at {}", len);
+	}
+}
@@ -0,0 +1,145 @@
+use std::path::PathBuf;
+use crate::helper;
+use crate::popup::PopupStyle;
+use iced::{
+	widget::{button, checkbox, column, container, row, text},
+	theme::Text::Color,
+	Alignment, Element, Length,
+};
+use rsfiles_core::{Conflict, ConflictAction, ConflictPolicy, CopyOptions, FileEntry, Job, TimeStyle};
+
+#[derive(Debug, Clone)]
+pub enum ConflictMessage {
+	Resolve(ConflictAction),
+	ApplyToAll(bool),
+	Cancel,
+}
+
+/// Asks what to do about every taken name of a copy or move, one after another,
+/// the job only starts once all of them are answered
+#[derive(Clone)]
+pub struct ConflictDialog {
+	sources: Vec<PathBuf>,
+	dest: PathBuf,
+	is_move: bool,
+	options: CopyOptions,
+	conflicts: Vec<Conflict>,
+	current: usize,
+	policy: ConflictPolicy,
+	apply_to_all: bool,
+}
+
+impl ConflictDialog {
+	pub fn new(sources: Vec<PathBuf>, dest: PathBuf, is_move: bool, options: CopyOptions, conflicts: Vec<Conflict>) -> Self {
+		Self {
+			sources,
+			dest,
+			is_move,
+			options,
+			conflicts,
+			current: 0,
+			policy: ConflictPolicy::new(),
+			apply_to_all: false,
+		}
+	}
+
+	/// The job with every decision in it once the last conflict is answered
+	pub fn update(&mut self, message: ConflictMessage) -> Option<Job> {
+		match message {
+			ConflictMessage::Resolve(action) => {
+				if self.apply_to_all {
+					self.policy.default = Some(action);
+					self.current = self.conflicts.len();
+				} else {
+					self.policy.decide(self.conflicts[self.current].source.path(), action);
+					self.current += 1;
+				}
+				(self.current == self.conflicts.len()).then(|| self.job())
+			}
+			ConflictMessage::ApplyToAll(apply) => {
+				self.apply_to_all = apply;
+				None
+			}
+			ConflictMessage::Cancel => None,
+		}
+	}
+
+	fn job(&self) -> Job {
+		let (sources, dest, conflicts) = (self.sources.clone(), self.dest.clone(), self.policy.clone());
+		let options = self.options;
+		if self.is_move {
+			Job::Move { sources, dest, conflicts, options }
+		} else {
+			Job::Copy { sources, dest, conflicts, options }
+		}
+	}
+
+	pub fn view(&self, time_style: TimeStyle) -> Element<'_, ConflictMessage> {
+		let conflict = &self.conflicts[self.current];
+		let folder = self.dest.file_name().map_or_else(
+			|| self.dest.display().to_string(),
+			|name| name.to_string_lossy().into_owned(),
+		);
+
+		let action_button = |label, action, style| {
+			button(text(label).size(12))
+				.on_press(ConflictMessage::Resolve(action))
+				.padding([4, 8])
+				.style(style)
+		};
+		let mut buttons = row![].spacing(8);
+		if !conflict.is_same_entry() {
+			buttons = buttons.push(action_button("Replace", ConflictAction::Replace, iced::theme::Button::Destructive));
+		}
+		buttons = buttons
+			.push(action_button("Skip", ConflictAction::Skip, iced::theme::Button::Secondary))
+			.push(action_button("Keep both", ConflictAction::KeepBoth, iced::theme::Button::Primary));
+		if conflict.can_merge() && !conflict.is_same_entry() {
+			buttons = buttons.push(action_button("Merge", ConflictAction::Merge, iced::theme::Button::Secondary));
+		}
+		buttons = buttons.push(
+			button(text("Cancel").size(12))
+				.on_press(ConflictMessage::Cancel)
+				.padding([4, 8])
+				.style(iced::theme::Button::Secondary),
+		);
+
+		let left = self.conflicts.len() - self.current;
+		let mut content = column![
+			text(format!("\"{}\" already exists in {}", conflict.existing.display_name(), folder)).size(14),
+			entry_details(if self.is_move { "Moving" } else { "Copying" }, &conflict.source, time_style),
+			entry_details("Existing", &conflict.existing, time_style),
+		]
+		.spacing(8);
+		if left > 1 {
+			content = content.push(
+				checkbox(format!("Do this for all {} conflicts", left), self.apply_to_all)
+					.on_toggle(ConflictMessage::ApplyToAll)
+					.size(14)
+					.text_size(12),
+			);
+		}
+		content = content.push(buttons);
+
+		container(content.padding(12).max_width(480))
+			.style(iced::theme::Container::Custom(Box::new(PopupStyle)))
+			.into()
+	}
+}
+
+/// "Existing: PDF file, 2.4 MB, 2024-05-01 13:45"
+fn entry_details(label: &str, file: &FileEntry, time_style: TimeStyle) -> Element<'static, ConflictMessage> {
+	let mut details = vec![helper::type_text(file)];
+	let size = helper::size_text(file, false);
+	if !size.is_empty() {
+		details.push(size);
+	}
+	details.push(helper::modified_text(file, time_style));
+
+	row![
+		text(format!("{}:", label)).size(12).width(Length::Fixed(70.0)),
+		text(details.join(", ")).size(12).style(Color(iced::Color::from_rgb(0.7, 0.7, 0.8))),
+	]
+	.align_items(Alignment::Center)
+	.into()
+}
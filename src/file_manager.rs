@@ -1,35 +1,89 @@
 
-use super::helper::{self, PathExt, Columns, FileEntry, copy_dir_all, get_file_display_info};
+use super::helper::{self, Columns, FocusedRowStyle, LoadEvent, get_file_display_info};
+use super::conflict::{ConflictDialog, ConflictMessage};
+use super::jobs::{JobMessage, Jobs};
 use super::popup::{Popup, PopupMessage, PopupState, OverlayStyle, calculate_popup_position};
-use super::navigation::NavigationState;
+use super::selection::{Lasso, Selection};
+use rsfiles_core::{
+	format_size, format_time_full, merge_sorted, read_file_entry, sort_directory_contents, CacheStats, CancelToken,
+	ChangeBatch, Collation, DirSize, FileEntry, FsChange, ListingCache, NavigationState, PathExt, ShortcutInfo, SizeCache,
+	SortColumn, SortOrder, TimeStyle,
+};
 use iced::{
 	alignment, keyboard, mouse, mouse::Button,
 	widget::{
 		scrollable,
-		scrollable::Viewport,
-		button, checkbox, column, container, mouse_area, row, text, text_input, Column,
+		scrollable::{AbsoluteOffset, RelativeOffset, Viewport},
+		tooltip,
+		button, checkbox, column, container, mouse_area, pick_list, row, text, text_input, Column, Space,
 	},
 	theme::Text::Color,
 	Alignment, Application, Command, Element, Event, Length, Point, Size, Subscription, Theme,
 };
-use std::{fs, path::PathBuf, time::SystemTime};
+use std::{collections::HashSet, fs, path::{Path, PathBuf}, time::{Duration, Instant, SystemTime}};
+
+// Limits of the listings kept for recently left folders
+const MAX_CACHED_DIRS: usize = 16;
+const MAX_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+// Every row of the file list has this height, so only the visible ones need widgets
+const ROW_HEIGHT: f32 = 32.0;
+// Rows built above and below the visible ones
+const ROW_BUFFER: usize = 8;
+// Blank margin left and right of the rows and below the last one, a rubber band starts there
+const LIST_GUTTER: u16 = 12;
+const LIST_BOTTOM_GAP: f32 = 2.0 * ROW_HEIGHT;
+// A rubber band at this distance from the list's top or bottom edge scrolls it, this far per tick
+const AUTO_SCROLL_EDGE: f32 = ROW_HEIGHT;
+const AUTO_SCROLL_STEP: f32 = ROW_HEIGHT / 2.0;
+// Letters typed within this time of each other are one name prefix to jump to
+const TYPE_AHEAD_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn file_list_id() -> scrollable::Id {
+	scrollable::Id::new("file_list")
+}
+
+fn path_input_id() -> text_input::Id {
+	text_input::Id::new("path_input")
+}
 
 pub struct FileManager {
 	pub navigation: NavigationState,
 	pub ui_state: UIState,
 	pub clipboard: Option<ClipboardItem>,
 	pub files: FileCache,
+	pub jobs: Jobs,
 }
 
 #[derive(Clone)]
 pub struct UIState {
-	pub selected_file: Option<PathBuf>,
+	pub selection: Selection,
+	// Held keyboard modifiers, clicks select differently with Ctrl and Shift
+	pub modifiers: keyboard::Modifiers,
+	// Last pointer position over the file list, in list coordinates
+	pub list_pointer: Option<Point>,
+	pub lasso: Option<Lasso>,
+	// Row of the keyboard cursor, it moves with the arrow keys and the last click
+	pub focused: Option<PathBuf>,
+	// Name prefix typed so far and when the last letter came
+	pub type_ahead: Option<(String, Instant)>,
 	pub hovered_file: Option<PathBuf>,
 	pub error_message: Option<String>,
+	// Folder whose changes can not be watched and why, shown again after each load of it
+	pub watch_error: Option<(PathBuf, String)>,
 	pub show_hidden: bool,
+	pub folder_sizes: bool,
+	pub time_style: TimeStyle,
+	// Pastes and moves read every copied file back and compare checksums
+	pub verify_copies: bool,
 	pub columns: Columns,
 	pub scroll_offset: f32,
+	// Absolute scroll position and height of the file list, in pixels
+	pub list_offset: f32,
+	pub list_height: f32,
 	pub popup: Option<Popup>,
+	// Asks about taken names before a paste starts
+	pub conflict: Option<ConflictDialog>,
 	pub mouse_position: Point,
 	pub loading: bool,
 	pub window_size: Size,
@@ -37,13 +91,66 @@ pub struct UIState {
 
 #[derive(Clone)]
 pub struct ClipboardItem {
-	path: PathBuf,
+	paths: Vec<PathBuf>,
 	is_cut: bool,
 }
 
 #[derive(Clone)]
 pub struct FileCache {
 	cached_files: Option<(PathBuf, Vec<FileEntry>, SystemTime)>,
+	// Bumped on every load, batches of older loads are dropped
+	generation: u64,
+	cancel: CancelToken,
+	// Entries of the current listing that could not be read
+	warnings: usize,
+	// Why the last entry that could not be listed at all was left out
+	last_warning: Option<String>,
+	// Folder sizes outlive the listing, they stay valid until the folder's mtime changes
+	sizes: SizeCache,
+	size_cancel: CancelToken,
+	// Changes reported while the listing was still loading, applied once it is done
+	pending_changes: Vec<FsChange>,
+	// Listing is fully read, only those go into `recent`
+	complete: bool,
+	// mtime of the listed folder from right before it was read
+	dir_modified: Option<SystemTime>,
+	// A cached listing is shown while the folder is read again into here (entries, listing errors)
+	revalidating: Option<(Vec<FileEntry>, usize)>,
+	recent: ListingCache,
+	// Positions of the entries the list shows, worked out again when the entries or
+	// `show_hidden` change instead of every time the list is looked at
+	shown: Vec<usize>,
+	show_hidden: bool,
+}
+
+/// Entries the list shows, in their order
+#[derive(Clone, Copy)]
+struct Shown<'a> {
+	files: &'a [FileEntry],
+	rows: &'a [usize],
+}
+
+impl<'a> Shown<'a> {
+	fn len(&self) -> usize {
+		self.rows.len()
+	}
+
+	fn iter(&self) -> impl Iterator<Item = &'a FileEntry> + Clone {
+		self.range(0..self.len())
+	}
+
+	fn range(&self, rows: std::ops::Range<usize>) -> impl Iterator<Item = &'a FileEntry> + Clone {
+		let files = self.files;
+		self.rows[rows].iter().map(move |&index| &files[index])
+	}
+}
+
+impl std::ops::Index<usize> for Shown<'_> {
+	type Output = FileEntry;
+
+	fn index(&self, row: usize) -> &FileEntry {
+		&self.files[self.rows[row]]
+	}
 }
 
 #[derive(Debug, Clone)]
@@ -64,6 +171,13 @@ pub enum Message {
 	FileHovered(PathBuf),
 	FileUnhovered,
 	DeleteSelected,
+	SelectAll,
+	ClearSelection,
+	MoveFocus(FocusMove),
+	OpenFocused,
+	// Letters typed over the list, they jump to a matching name
+	TypeAhead(String),
+	FocusPathBar,
 	
 	// Clipboard operations
 	CopySelected,
@@ -74,29 +188,71 @@ pub enum Message {
 	// UI state
 	Refresh,
 	ToggleHidden,
+	ToggleFolderSizes,
+	TimeStyleChanged(TimeStyle),
+	ToggleVerifyCopies,
+	SortBy(SortColumn),
+	ToggleFoldersFirst,
+	ToggleNaturalOrder,
+	SecondarySortChanged(SortColumn),
 	ScrollChanged(Viewport),
 	MouseMoved(Point),
 	WindowResized(Size),
 	OverlayClicked,
 	MouseButtonPressed(mouse::Button),
+	MouseButtonReleased(mouse::Button),
+	// Press on blank space of the file list and pointer moves over it
+	ListPressed,
+	ListPointerMoved(Point),
+	AutoScrollTick,
+	ModifiersChanged(keyboard::Modifiers),
+	EscapePressed,
 	
 	// Async operations
-	FilesLoaded(Result<Vec<FileEntry>, String>),
+	FilesLoaded(u64, LoadEvent),
+	FolderSizeLoaded(u64, PathBuf, DirSize),
+	DirectoryChanged(PathBuf, ChangeBatch),
+	WatchFailed(PathBuf, String),
+	Job(u64, JobMessage),
 	
 	// Popup
 	PopupMessage(PopupMessage),
+	Conflict(ConflictMessage),
+}
+
+/// Where a key moves the keyboard cursor of the file list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusMove {
+	Up,
+	Down,
+	PageUp,
+	PageDown,
+	Home,
+	End,
 }
 
 impl UIState {
 	pub fn new() -> Self {
 		Self {
-			selected_file: None,
+			selection: Selection::new(),
+			modifiers: keyboard::Modifiers::default(),
+			list_pointer: None,
+			lasso: None,
+			focused: None,
+			type_ahead: None,
 			hovered_file: None,
 			error_message: None,
+			watch_error: None,
 			show_hidden: false,
+			folder_sizes: false,
+			time_style: TimeStyle::default(),
+			verify_copies: false,
 			columns: Columns::new(),
 			scroll_offset: 0.0,
+			list_offset: 0.0,
+			list_height: 0.0,
 			popup: None,
+			conflict: None,
 			mouse_position: Point::ORIGIN,
 			loading: true,
 			window_size: Size::new(800.0, 600.0),
@@ -105,9 +261,12 @@ impl UIState {
 
 	pub fn clear_transient_state(&mut self) {
 		self.popup = None;
-		self.selected_file = None;
+		self.selection.clear();
+		self.focused = None;
+		self.type_ahead = None;
 		self.error_message = None;
 		self.scroll_offset = 0.0;
+		self.list_offset = 0.0;
 	}
 
 	pub fn set_error(&mut self, message: String) {
@@ -119,6 +278,19 @@ impl FileCache {
 	fn new() -> Self {
 		Self {
 			cached_files: None,
+			generation: 0,
+			cancel: CancelToken::new(),
+			warnings: 0,
+			last_warning: None,
+			sizes: SizeCache::new(),
+			size_cancel: CancelToken::new(),
+			pending_changes: Vec::new(),
+			complete: false,
+			dir_modified: None,
+			revalidating: None,
+			recent: ListingCache::new(MAX_CACHED_DIRS, MAX_CACHE_BYTES),
+			shown: Vec::new(),
+			show_hidden: false,
 		}
 	}
 
@@ -126,13 +298,234 @@ impl FileCache {
 		self.cached_files.as_ref().map(|(_, files, _)| files)
 	}
 
-	fn update_cache(&mut self, path: PathBuf, files: Vec<FileEntry>) {
+	fn len(&self) -> usize {
+		self.get_files().map_or(0, Vec::len)
+	}
+
+	/// Entries the list shows, in their order
+	fn visible(&self) -> Shown<'_> {
+		Shown { files: self.get_files().map_or(&[], Vec::as_slice), rows: &self.shown }
+	}
+
+	fn set_show_hidden(&mut self, show_hidden: bool) {
+		self.show_hidden = show_hidden;
+		self.update_shown();
+	}
+
+	/// Works out which entries the list shows, after they changed
+	fn update_shown(&mut self) {
+		self.shown.clear();
+		if let Some((_, files, _)) = &self.cached_files {
+			let show_hidden = self.show_hidden;
+			self.shown.extend(files.iter().enumerate().filter(|(_, f)| show_hidden || !f.is_hidden()).map(|(index, _)| index));
+		}
+	}
+
+	fn find(&self, path: &Path) -> Option<&FileEntry> {
+		self.get_files()?.iter().find(|f| f.as_path() == path)
+	}
+
+	/// Cancels the load still in progress and starts an empty listing for `path`
+	fn begin_load(&mut self, path: PathBuf) -> (u64, CancelToken) {
+		self.stash_current(&path);
+		let load = self.next_generation();
+		self.warnings = 0;
+		self.last_warning = None;
+		self.dir_modified = directory_mtime(&path);
+		self.cached_files = Some((path, Vec::new(), SystemTime::now()));
+		self.update_shown();
+		load
+	}
+
+	/// Shows the cached listing of `path` when the folder did not change since it was left,
+	/// the returned load reads it again in the background and replaces it once done
+	fn begin_cached_load(&mut self, path: PathBuf, order: &SortOrder) -> Option<(u64, CancelToken)> {
+		self.stash_current(&path);
+		let (mut files, modified) = self.recent.take(&path)?;
+
+		let load = self.next_generation();
+		sort_directory_contents(&mut files, order);
+		self.warnings = files.iter().filter(|f| f.is_unreadable()).count();
+		self.last_warning = None;
+		self.dir_modified = Some(modified);
 		self.cached_files = Some((path, files, SystemTime::now()));
+		self.complete = true;
+		self.revalidating = Some((Vec::new(), 0));
+		self.update_shown();
+		Some(load)
+	}
+
+	/// Cancels everything still running for the current listing
+	fn next_generation(&mut self) -> (u64, CancelToken) {
+		self.cancel.cancel();
+		self.size_cancel.cancel();
+		self.cancel = CancelToken::new();
+		self.generation += 1;
+		self.pending_changes.clear();
+		self.complete = false;
+		self.revalidating = None;
+		(self.generation, self.cancel.clone())
+	}
+
+	/// Keeps the listing that is about to be replaced by one of `next` for going back later
+	fn stash_current(&mut self, next: &Path) {
+		if let Some((path, files, _)) = self.cached_files.take()
+			&& self.complete
+			&& path != next
+			&& let Some(modified) = self.dir_modified {
+			self.recent.insert(path, files, modified);
+		}
+	}
+
+	/// The listing is fully read, a background read of a cached one replaces it now
+	fn finish_load(&mut self, order: &SortOrder) {
+		self.complete = true;
+		if let Some((mut files, errors)) = self.revalidating.take()
+			&& let Some((_, cached, time)) = &mut self.cached_files {
+			sort_directory_contents(&mut files, order);
+			self.warnings = errors + files.iter().filter(|f| f.is_unreadable()).count();
+			*cached = files;
+			*time = SystemTime::now();
+			self.update_shown();
+		}
+	}
+
+	/// Still reading the folder, either for the first time or to check a cached listing
+	fn is_loading(&self) -> bool {
+		!self.complete || self.revalidating.is_some()
+	}
+
+	fn is_revalidating(&self) -> bool {
+		self.revalidating.is_some()
+	}
+
+	fn stats(&self) -> CacheStats {
+		self.recent.stats()
+	}
+
+	fn add_warning(&mut self, warning: String) {
+		self.last_warning = Some(warning);
+		match &mut self.revalidating {
+			Some((_, errors)) => *errors += 1,
+			None => self.warnings += 1,
+		}
+	}
+
+	fn warnings(&self) -> usize {
+		self.warnings
+	}
+
+	fn is_current(&self, generation: u64) -> bool {
+		self.generation == generation
+	}
+
+	fn append(&mut self, files: Vec<FileEntry>, order: &SortOrder) {
+		if let Some((fresh, _)) = &mut self.revalidating {
+			fresh.extend(files);
+			return;
+		}
+		self.warnings += files.iter().filter(|f| f.is_unreadable()).count();
+		if let Some((_, cached, time)) = &mut self.cached_files {
+			merge_sorted(cached, files, order);
+			*time = SystemTime::now();
+			self.update_shown();
+		}
+	}
+
+	fn sort(&mut self, order: &SortOrder) {
+		if let Some((_, cached, _)) = &mut self.cached_files {
+			sort_directory_contents(cached, order);
+			self.update_shown();
+		}
 	}
 
 	fn clear(&mut self) {
 		self.cached_files = None;
+		self.update_shown();
+	}
+
+	/// Reads the changed entries again and puts them in place, entries that are gone are dropped.
+	/// Returns whether a folder was added or changed, its size is not known then.
+	fn apply_changes(&mut self, changes: &[FsChange], order: &SortOrder) -> bool {
+		let Some((path, cached, time)) = &mut self.cached_files else { return false };
+		// The listing is up to date with the folder again
+		self.dir_modified = directory_mtime(path);
+		let paths: HashSet<&PathBuf> = changes.iter().flat_map(FsChange::paths).collect();
+
+		let before = cached.len();
+		let removed_warnings = cached.iter()
+			.filter(|f| f.is_unreadable() && paths.contains(&f.path()))
+			.count();
+		cached.retain(|f| !paths.contains(&f.path()));
+		self.warnings -= removed_warnings.min(self.warnings);
+
+		let updated: Vec<FileEntry> = paths.into_iter().filter_map(|path| read_file_entry(path)).collect();
+		self.warnings += updated.iter().filter(|f| f.is_unreadable()).count();
+		let folder_changed = updated.iter().any(FileEntry::is_dir);
+		if before != cached.len() || !updated.is_empty() {
+			merge_sorted(cached, updated, order);
+			*time = SystemTime::now();
+			self.update_shown();
+		}
+		folder_changed
+	}
+
+	/// Restarts the folder size calculation, sizes still valid in the cache are filled
+	/// in right away and the folders that need a walk are returned
+	fn begin_sizes(&mut self) -> (Vec<PathBuf>, u64, CancelToken) {
+		self.size_cancel.cancel();
+		self.size_cancel = CancelToken::new();
+
+		let (mut pending, show_hidden) = (Vec::new(), self.show_hidden);
+		if let Some((_, cached, _)) = &mut self.cached_files {
+			let dirs = cached.iter_mut().filter(|f| {
+				f.is_dir() && !f.is_symlink() && !f.is_unreadable() && (show_hidden || !f.is_hidden())
+			});
+			for file in dirs {
+				let size = file.modified().and_then(|modified| self.sizes.get(&file.path(), modified));
+				if size.is_some() {
+					file.set_dir_size(size);
+				} else if file.dir_size().is_none() {
+					pending.push(file.path());
+				}
+			}
+		}
+		(pending, self.generation, self.size_cancel.clone())
+	}
+
+	fn set_dir_size(&mut self, path: PathBuf, size: DirSize) {
+		let Some((_, cached, _)) = &mut self.cached_files else { return };
+		if let Some(file) = cached.iter_mut().find(|f| f.path() == path) {
+			file.set_dir_size(Some(size));
+			if let Some(modified) = file.modified() {
+				self.sizes.insert(path, modified, size);
+			}
+		}
 	}
+
+	/// Stops the calculation and forgets the sizes shown in the listing
+	fn clear_dir_sizes(&mut self) {
+		self.size_cancel.cancel();
+		if let Some((_, cached, _)) = &mut self.cached_files {
+			cached.iter_mut().for_each(|f| f.set_dir_size(None));
+		}
+	}
+
+	fn clear_size_cache(&mut self) {
+		self.sizes.clear();
+	}
+}
+
+/// First error of a batch operation, with how many more there were
+pub fn summarize_errors(errors: Vec<String>) -> String {
+	match errors.len() {
+		0 | 1 => errors.into_iter().next().unwrap_or_default(),
+		n => format!("{} (and {} more errors)", errors[0], n - 1),
+	}
+}
+
+fn directory_mtime(path: &Path) -> Option<SystemTime> {
+	fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
 }
 
 impl Application for FileManager {
@@ -142,18 +535,16 @@ impl Application for FileManager {
 	type Flags = ();
 
 	fn new(_flags: ()) -> (Self, Command<Message>) {
-		let navigation = NavigationState::new();
-		let load_command = helper::load_files_sync(navigation.current_path.clone());
-
-		(
-			Self {
-				navigation,
-				ui_state: UIState::new(),
-				clipboard: None,
-				files: FileCache::new(),
-			},
-			load_command,
-		)
+		let mut file_manager = Self {
+			navigation: NavigationState::new(),
+			ui_state: UIState::new(),
+			clipboard: None,
+			files: FileCache::new(),
+			jobs: Jobs::new(),
+		};
+		let load_command = file_manager.load_current_directory();
+
+		(file_manager, load_command)
 	}
 
 	fn title(&self) -> String {
@@ -190,12 +581,35 @@ impl Application for FileManager {
 				Command::none()
 			}
 			Message::DeleteSelected => {
-				if let Some(selected) = &self.ui_state.selected_file {
-					self.delete_file(selected.clone())
-				} else {
+				if self.ui_state.selection.is_empty() {
 					Command::none()
+				} else {
+					self.delete_files(self.ui_state.selection.paths())
 				}
 			},
+			Message::SelectAll => {
+				let files = self.files.visible();
+				self.ui_state.selection.select_all(files.iter());
+				Command::none()
+			}
+			Message::ClearSelection => {
+				self.ui_state.selection.clear();
+				Command::none()
+			}
+			Message::MoveFocus(movement) => self.handle_move_focus(movement),
+			Message::OpenFocused => {
+				let focused = self.ui_state.focused.clone()
+					.or_else(|| self.ui_state.selection.single().cloned());
+				match focused {
+					Some(path) if self.ui_state.popup.is_none() => self.handle_double_click(path),
+					_ => Command::none(),
+				}
+			}
+			Message::TypeAhead(letters) => self.handle_type_ahead(letters),
+			Message::FocusPathBar => {
+				self.ui_state.popup = None;
+				Command::batch([text_input::focus(path_input_id()), text_input::select_all(path_input_id())])
+			}
 
 			// Clipboard operations
 			Message::CopySelected => self.handle_copy(),
@@ -207,14 +621,59 @@ impl Application for FileManager {
 			}
 
 			// UI state
-			Message::Refresh => self.refresh_directory(),
+			Message::Refresh => {
+				// Changes deep inside a folder don't touch its mtime, a manual refresh walks again
+				self.files.clear_size_cache();
+				self.refresh_directory()
+			}
 			Message::ToggleHidden => {
 				self.ui_state.show_hidden = !self.ui_state.show_hidden;
+				self.files.set_show_hidden(self.ui_state.show_hidden);
+				self.load_folder_sizes()
+			}
+			Message::ToggleFolderSizes => {
+				self.ui_state.folder_sizes = !self.ui_state.folder_sizes;
+				if self.ui_state.folder_sizes {
+					self.load_folder_sizes()
+				} else {
+					self.files.clear_dir_sizes();
+					self.files.sort(&self.navigation.sort_order());
+					Command::none()
+				}
+			}
+			Message::TimeStyleChanged(style) => {
+				self.ui_state.time_style = style;
+				Command::none()
+			}
+			Message::ToggleVerifyCopies => {
+				self.ui_state.verify_copies = !self.ui_state.verify_copies;
 				Command::none()
 			}
+			Message::SortBy(column) => {
+				let order = self.navigation.sort_order().toggled(column);
+				self.apply_sort_order(order)
+			}
+			Message::ToggleFoldersFirst => {
+				let order = self.navigation.sort_order();
+				self.apply_sort_order(SortOrder { folders_first: !order.folders_first, ..order })
+			}
+			Message::ToggleNaturalOrder => {
+				let order = self.navigation.sort_order();
+				let collation = match order.collation {
+					Collation::Natural => Collation::Bytes,
+					Collation::Bytes => Collation::Natural,
+				};
+				self.apply_sort_order(SortOrder { collation, ..order })
+			}
+			Message::SecondarySortChanged(column) => {
+				let order = self.navigation.sort_order();
+				self.apply_sort_order(SortOrder { secondary: column, ..order })
+			}
 			Message::ScrollChanged(viewport) => {
 				self.ui_state.popup = None;
 				self.ui_state.scroll_offset = viewport.relative_offset().y;
+				self.ui_state.list_offset = viewport.absolute_offset().y;
+				self.ui_state.list_height = viewport.bounds().height;
 				Command::none()
 			}
 			Message::MouseMoved(position) => {
@@ -231,24 +690,105 @@ impl Application for FileManager {
 				Command::none()
 			}
 			Message::MouseButtonPressed(button) => self.handle_mouse_button(button),
+			Message::MouseButtonReleased(Button::Left) => {
+				self.ui_state.lasso = None;
+				Command::none()
+			}
+			Message::MouseButtonReleased(_) => Command::none(),
+			Message::ListPressed => self.handle_list_pressed(),
+			Message::ListPointerMoved(position) => {
+				self.ui_state.list_pointer = Some(position);
+				if let Some(lasso) = &mut self.ui_state.lasso {
+					lasso.end = position.y;
+					self.update_lasso_selection();
+				}
+				Command::none()
+			}
+			Message::AutoScrollTick => self.handle_auto_scroll(),
+			Message::ModifiersChanged(modifiers) => {
+				self.ui_state.modifiers = modifiers;
+				Command::none()
+			}
+			Message::EscapePressed => {
+				if self.ui_state.conflict.is_some() {
+					self.ui_state.conflict = None;
+				} else if self.ui_state.popup.is_some() {
+					self.ui_state.popup = None;
+				} else {
+					self.ui_state.selection.clear();
+				}
+				Command::none()
+			}
 
 			// Async operations
-			Message::FilesLoaded(result) => self.handle_files_loaded(result),
+			Message::FilesLoaded(generation, event) => self.handle_files_loaded(generation, event),
+			Message::DirectoryChanged(path, batch) => self.handle_directory_changed(path, batch),
+			Message::WatchFailed(path, error) => {
+				let message = format!("Changes to this folder are not shown: {}", error);
+				if path == self.navigation.current_path {
+					self.ui_state.set_error(message.clone());
+				}
+				self.ui_state.watch_error = Some((path, message));
+				Command::none()
+			}
+			Message::Job(id, message) => self.handle_job_message(id, message),
+			Message::FolderSizeLoaded(generation, path, size) => {
+				if self.files.is_current(generation) {
+					self.files.set_dir_size(path.clone(), size);
+					if let Some(file) = self.files.find(&path) {
+						self.ui_state.selection.update_size(file);
+					}
+					let order = self.navigation.sort_order();
+					if order.column == SortColumn::Size || order.secondary == SortColumn::Size {
+						self.files.sort(&order);
+					}
+				}
+				Command::none()
+			}
 
 			// Popup
 			Message::PopupMessage(popup_msg) => self.handle_popup_message(popup_msg),
+			Message::Conflict(ConflictMessage::Cancel) => {
+				self.ui_state.conflict = None;
+				Command::none()
+			}
+			Message::Conflict(message) => {
+				let job = self.ui_state.conflict.as_mut().and_then(|dialog| dialog.update(message));
+				match job {
+					Some(job) => {
+						self.ui_state.conflict = None;
+						self.start_job(job)
+					}
+					None => Command::none(),
+				}
+			}
 		}
 	}
 
-	fn view(&self) -> Element<Message> {
+	fn view(&self) -> Element<'_, Message> {
 		let control_panel = self.view_control_panel();
 		let file_list = self.view_file_list();
 
 		let main_content = column![control_panel, file_list]
+			.push_maybe(self.view_jobs())
 			.width(Length::Fill)
 			.height(Length::Fill);
 
-		if let Some(popup) = &self.ui_state.popup {
+		if let Some(dialog) = &self.ui_state.conflict {
+			let dialog_view = dialog.view(self.ui_state.time_style).map(Message::Conflict);
+			// Has to be answered, clicks next to it do nothing
+			let overlay = container(dialog_view)
+				.width(Length::Fill)
+				.height(Length::Fill)
+				.center_x()
+				.center_y()
+				.style(iced::theme::Container::Custom(Box::new(OverlayStyle)));
+
+			container(column![main_content, overlay])
+				.width(Length::Fill)
+				.height(Length::Fill)
+				.into()
+		} else if let Some(popup) = &self.ui_state.popup {
 			let popup_view = popup.view().map(Message::PopupMessage);
 			let overlay = container(popup_view)
 				.width(Length::Fill)
@@ -271,6 +811,13 @@ impl Application for FileManager {
 		Subscription::batch([
 			self.keyboard_subscription(),
 			self.event_subscription(),
+			helper::watch_files(self.navigation.current_path.clone()),
+			self.jobs.subscription(),
+			if self.auto_scroll_direction() != 0.0 {
+				helper::auto_scroll_ticks()
+			} else {
+				Subscription::none()
+			},
 		])
 	}
 }
@@ -305,10 +852,8 @@ impl FileManager {
 
 	fn handle_navigate_back(&mut self) -> Command<Message> {
 		if let Some(history) = self.navigation.go_back() {
-			self.files.clear();
-			self.ui_state.loading = true;
 			// Don't set scroll_offset here - wait for files to load
-			let msg = helper::load_files_sync(self.navigation.current_path.clone());
+			let msg = self.open_current_directory();
 			self.ui_state.scroll_offset = history.scroll;
 			println!("ff {:?}", self.ui_state.scroll_offset);
 			msg
@@ -319,10 +864,8 @@ impl FileManager {
 
 	fn handle_navigate_forward(&mut self) -> Command<Message> {
 		if let Some(history) = self.navigation.go_forward() {
-			self.files.clear();
-			self.ui_state.loading = true;
 			// Don't set scroll_offset here - wait for files to load
-			let msg = helper::load_files_sync(self.navigation.current_path.clone());
+			let msg = self.open_current_directory();
 			self.ui_state.scroll_offset = history.scroll;
 			println!("ff {:?}", self.ui_state.scroll_offset);
 			msg
@@ -338,30 +881,52 @@ impl FileManager {
 
 	fn handle_file_click(&mut self, path: PathBuf) -> Command<Message> {
 		self.ui_state.popup = None;
+		let Some(file) = self.files.find(&path).cloned() else {
+			return Command::none();
+		};
 
-		if self.ui_state.selected_file.as_ref() == Some(&path) {
+		self.ui_state.focused = Some(path.clone());
+		let modifiers = self.ui_state.modifiers;
+		if modifiers.shift() {
+			let files = self.files.visible();
+			self.ui_state.selection.select_range(files.iter(), &file, modifiers.command());
+		} else if modifiers.command() {
+			self.ui_state.selection.toggle(&file);
+		} else if self.ui_state.selection.single() == Some(&path) {
 			// Second click - navigate or handle shortcut
-			self.handle_double_click(path)
+			return self.handle_double_click(path);
 		} else {
 			// First click - select file
-			self.ui_state.selected_file = Some(path);
-			Command::none()
+			self.ui_state.selection.select_only(&file);
 		}
+		Command::none()
 	}
 
 	fn handle_double_click(&mut self, path: PathBuf) -> Command<Message> {
-		if path.is_dir() {
-			self.navigate_to_path(path)
-		} else if path.is_shortcut() {
+		if path.is_link() {
 			self.handle_shortcut_navigation(path)
+		} else if path.is_dir() {
+			self.navigate_to_path(path)
 		} else {
-			self.ui_state.selected_file = None;
+			self.ui_state.selection.clear();
 			Command::none()
 		}
 	}
 
 	fn handle_shortcut_navigation(&mut self, path: PathBuf) -> Command<Message> {
-		if let Some(target_path) = helper::resolve_shortcut(&path) {
+		let target_path = if path.is_symlink() {
+			path.symlink_target()
+		} else {
+			match ShortcutInfo::from_path(&path) {
+				Ok(info) => info.resolve(&path),
+				Err(e) => {
+					self.ui_state.set_error(e.to_string());
+					return Command::none();
+				}
+			}
+		};
+
+		if let Some(target_path) = target_path {
 			if target_path.exists() {
 				if target_path.is_dir() {
 					self.navigate_to_path(target_path)
@@ -385,8 +950,15 @@ impl FileManager {
 	}
 
 	fn handle_right_click(&mut self, path: PathBuf, position: Point) -> Command<Message> {
+		// The menu acts on the whole selection, unless the click was outside of it
+		if !self.ui_state.selection.contains(&path)
+			&& let Some(file) = self.files.find(&path) {
+			self.ui_state.selection.select_only(file);
+		}
+
 		let popup_state = PopupState {
 			file_path: path,
+			selection: self.ui_state.selection.paths(),
 			position: calculate_popup_position(position, self.ui_state.window_size),
 		};
 		self.ui_state.popup = Some(Popup::new(popup_state));
@@ -394,9 +966,9 @@ impl FileManager {
 	}
 
 	fn handle_copy(&mut self) -> Command<Message> {
-		if let Some(selected) = &self.ui_state.selected_file {
+		if !self.ui_state.selection.is_empty() {
 			self.clipboard = Some(ClipboardItem {
-				path: selected.clone(),
+				paths: self.ui_state.selection.paths(),
 				is_cut: false,
 			});
 			self.ui_state.popup = None;
@@ -405,9 +977,9 @@ impl FileManager {
 	}
 
 	fn handle_cut(&mut self) -> Command<Message> {
-		if let Some(selected) = &self.ui_state.selected_file {
+		if !self.ui_state.selection.is_empty() {
 			self.clipboard = Some(ClipboardItem {
-				path: selected.clone(),
+				paths: self.ui_state.selection.paths(),
 				is_cut: true,
 			});
 			self.ui_state.popup = None;
@@ -415,35 +987,153 @@ impl FileManager {
 		Command::none()
 	}
 
+	/// Copies or moves the clipboard into the current folder as a background job
 	fn handle_paste(&mut self) -> Command<Message> {
-		if let Some(clipboard_item) = &self.clipboard {
-			let dest_path = self.navigation.current_path.join(
-				clipboard_item.path.file_name().unwrap()
-			);
-
-			let result = if clipboard_item.is_cut {
-				fs::rename(&clipboard_item.path, &dest_path)
-					.map_err(|e| format!("Error moving file: {}", e))
-			} else {
-				self.copy_file_or_dir(&clipboard_item.path, &dest_path)
-					.map_err(|e| format!("Error copying file: {}", e))
-			};
+		let Some(clipboard_item) = &self.clipboard else {
+			return Command::none();
+		};
 
-			match result {
-				Ok(_) => {
-					if clipboard_item.is_cut {
-						self.clipboard = None;
-					}
-					self.refresh_directory()
-				}
-				Err(msg) => {
-					self.ui_state.set_error(msg);
-					Command::none()
-				}
-			}
+		let (sources, is_move) = (clipboard_item.paths.clone(), clipboard_item.is_cut);
+		if is_move {
+			// The entries are gone from where they were cut, pasting them again makes no sense
+			self.clipboard = None;
+		}
+		self.start_transfer(sources, self.navigation.current_path.clone(), is_move)
+	}
+
+	/// Moves the keyboard cursor. Shift selects everything from the anchor to it,
+	/// Ctrl only moves it and without either the row under it is selected.
+	fn handle_move_focus(&mut self, movement: FocusMove) -> Command<Message> {
+		self.ui_state.popup = None;
+		let files = self.files.visible();
+		let Some(last) = files.len().checked_sub(1) else {
+			return Command::none();
+		};
+
+		let current = self.ui_state.focused.as_ref()
+			.and_then(|focused| files.iter().position(|f| f.as_path() == focused));
+		let page = ((self.list_height() / ROW_HEIGHT) as usize).max(2) - 1;
+		let index = match (movement, current) {
+			(FocusMove::Home, _) => 0,
+			(FocusMove::End, _) => last,
+			(_, None) => 0,
+			(FocusMove::Up, Some(i)) => i.saturating_sub(1),
+			(FocusMove::Down, Some(i)) => (i + 1).min(last),
+			(FocusMove::PageUp, Some(i)) => i.saturating_sub(page),
+			(FocusMove::PageDown, Some(i)) => (i + page).min(last),
+		};
+
+		let file = &files[index];
+		let modifiers = self.ui_state.modifiers;
+		if modifiers.shift() {
+			self.ui_state.selection.select_range(files.iter(), file, modifiers.command());
+		} else if !modifiers.command() {
+			self.ui_state.selection.select_only(file);
+		}
+		self.ui_state.focused = Some(file.path());
+		self.scroll_row_into_view(index)
+	}
+
+	/// Focuses and selects the first name starting with the letters typed so far
+	fn handle_type_ahead(&mut self, letters: String) -> Command<Message> {
+		if self.ui_state.popup.is_some() {
+			return Command::none();
+		}
+		let now = Instant::now();
+		let mut prefix = match self.ui_state.type_ahead.take() {
+			Some((prefix, last)) if now.duration_since(last) < TYPE_AHEAD_TIMEOUT => prefix,
+			// A space only continues a name
+			_ if letters.trim().is_empty() => return Command::none(),
+			_ => String::new(),
+		};
+		prefix.push_str(&letters.to_lowercase());
+
+		let files = self.files.visible();
+		let found = files.iter().position(|file| file.display_name().to_lowercase().starts_with(&prefix));
+		self.ui_state.type_ahead = Some((prefix, now));
+		let Some(index) = found else {
+			return Command::none();
+		};
+
+		let file = &files[index];
+		self.ui_state.selection.select_only(file);
+		self.ui_state.focused = Some(file.path());
+		self.scroll_row_into_view(index)
+	}
+
+	/// Scrolls the file list just enough for the row to be fully visible
+	fn scroll_row_into_view(&mut self, index: usize) -> Command<Message> {
+		let top = index as f32 * ROW_HEIGHT;
+		let height = self.list_height();
+		let offset = if top < self.ui_state.list_offset {
+			top
+		} else if top + ROW_HEIGHT > self.ui_state.list_offset + height {
+			top + ROW_HEIGHT - height
 		} else {
-			Command::none()
+			return Command::none();
+		};
+		self.scroll_list_to_offset(offset)
+	}
+
+	/// Starts a rubber band, with Ctrl it adds to the current selection
+	fn handle_list_pressed(&mut self) -> Command<Message> {
+		self.ui_state.popup = None;
+		let Some(pointer) = self.ui_state.list_pointer else {
+			return Command::none();
+		};
+
+		let base = if self.ui_state.modifiers.command() {
+			self.ui_state.selection.clone()
+		} else {
+			Selection::new()
+		};
+		self.ui_state.lasso = Some(Lasso { start: pointer.y, end: pointer.y, base });
+		self.update_lasso_selection();
+		Command::none()
+	}
+
+	fn update_lasso_selection(&mut self) {
+		let Some(lasso) = &self.ui_state.lasso else { return };
+		let files = self.files.visible();
+
+		let mut selection = lasso.base.clone();
+		selection.extend(files.range(lasso.rows(ROW_HEIGHT, files.len())));
+		self.ui_state.selection = selection;
+	}
+
+	/// -1 while a rubber band is held at the top edge of the list, 1 at the bottom edge
+	fn auto_scroll_direction(&self) -> f32 {
+		let (Some(_), Some(pointer)) = (&self.ui_state.lasso, self.ui_state.list_pointer) else {
+			return 0.0;
+		};
+		let top = self.ui_state.list_offset;
+		if pointer.y < top + AUTO_SCROLL_EDGE {
+			-1.0
+		} else if pointer.y > top + self.list_height() - AUTO_SCROLL_EDGE {
+			1.0
+		} else {
+			0.0
+		}
+	}
+
+	/// Scrolls a bit towards the edge the rubber band is held at, the band grows along
+	fn handle_auto_scroll(&mut self) -> Command<Message> {
+		let previous = self.ui_state.list_offset;
+		let scroll = self.scroll_list_to_offset(previous + self.auto_scroll_direction() * AUTO_SCROLL_STEP);
+		let delta = self.ui_state.list_offset - previous;
+		if delta == 0.0 {
+			return Command::none();
+		}
+
+		// The pointer stays put on screen, so it moved over the list by the same amount
+		if let Some(pointer) = &mut self.ui_state.list_pointer {
+			pointer.y += delta;
+		}
+		if let Some(lasso) = &mut self.ui_state.lasso {
+			lasso.end += delta;
 		}
+		self.update_lasso_selection();
+		scroll
 	}
 
 	fn handle_mouse_button(&mut self, button: Button) -> Command<Message> {
@@ -454,22 +1144,92 @@ impl FileManager {
 		}
 	}
 
-	fn handle_files_loaded(&mut self, result: Result<Vec<FileEntry>, String>) -> Command<Message> {
-		self.ui_state.loading = false;
-		match result {
-			Ok(files) => {
-				self.files.update_cache(self.navigation.current_path.clone(), files);
-				self.ui_state.error_message = None;
-				// Restore scroll position after files are loaded
-				self.ui_state.scroll_offset = self.navigation.get_current_scroll();
-			}
-			Err(error) => {
+	fn handle_files_loaded(&mut self, generation: u64, event: LoadEvent) -> Command<Message> {
+		// Results of a folder we already left
+		if !self.files.is_current(generation) {
+			return Command::none();
+		}
+
+		match event {
+			LoadEvent::Batch(files) => {
+				self.files.append(files, &self.navigation.sort_order());
+			}
+			LoadEvent::Warning(warning) => {
+				self.files.add_warning(warning);
+			}
+			LoadEvent::Finished => {
+				let order = self.navigation.sort_order();
+				// A cached listing was on screen the whole time, don't jump around
+				let restore_scroll = !self.files.is_revalidating();
+				self.ui_state.loading = false;
+				self.files.finish_load(&order);
+				let changes = std::mem::take(&mut self.files.pending_changes);
+				self.files.apply_changes(&changes, &order);
+
+				if restore_scroll {
+					// A folder that is not watched keeps saying so
+					self.ui_state.error_message = self.ui_state.watch_error.as_ref()
+						.filter(|(path, _)| *path == self.navigation.current_path)
+						.map(|(_, error)| error.clone());
+					// Restore scroll position after files are loaded
+					let scroll = self.scroll_list_to(self.navigation.get_current_scroll());
+					return Command::batch([scroll, self.load_folder_sizes()]);
+				}
+				return self.load_folder_sizes();
+			}
+			LoadEvent::Failed(error) => {
+				self.ui_state.loading = false;
+				// A cached listing of a folder that can not be read anymore is no use either
+				if self.files.len() == 0 || self.files.is_revalidating() {
+					self.files.clear();
+				}
 				self.ui_state.set_error(error);
 			}
 		}
 		Command::none()
 	}
 
+	/// Puts changes made by other programs into the listing without reloading it,
+	/// selection and scroll position stay where they are
+	fn handle_directory_changed(&mut self, path: PathBuf, batch: ChangeBatch) -> Command<Message> {
+		if path != self.navigation.current_path {
+			return Command::none();
+		}
+
+		if batch.rescan {
+			if self.files.is_loading() {
+				return Command::none();
+			}
+			self.navigation.update_current_scroll(self.ui_state.scroll_offset);
+			return self.load_current_directory();
+		}
+
+		for change in &batch.changes {
+			match change {
+				FsChange::Renamed(from, to) => self.ui_state.selection.rename(from, to.clone()),
+				FsChange::Removed(removed) => self.ui_state.selection.remove(removed),
+				_ => {}
+			}
+		}
+
+		if self.files.is_loading() {
+			self.files.pending_changes.extend(batch.changes);
+			return Command::none();
+		}
+		if self.files.apply_changes(&batch.changes, &self.navigation.sort_order()) {
+			self.load_folder_sizes()
+		} else {
+			Command::none()
+		}
+	}
+
+	/// Remembers the order for the current directory and re-sorts what is already loaded
+	fn apply_sort_order(&mut self, order: SortOrder) -> Command<Message> {
+		self.navigation.set_sort_order(order);
+		self.files.sort(&order);
+		Command::none()
+	}
+
 	fn handle_popup_message(&mut self, popup_msg: PopupMessage) -> Command<Message> {
 		if self.ui_state.popup.is_some() {
 			match popup_msg {
@@ -481,12 +1241,13 @@ impl FileManager {
 					self.ui_state.popup = None;
 					Command::none()
 				}
+				PopupMessage::Copy => self.handle_copy(),
+				PopupMessage::Cut => self.handle_cut(),
+				PopupMessage::Delete => self.update(Message::DeleteSelected),
 				_ => {
-					if let Some(popup) = &mut self.ui_state.popup {
-						if let Some(new_path) = popup.update(popup_msg) {
-							self.ui_state.selected_file = Some(new_path);
-							return self.refresh_directory();
-						}
+					if let Some(popup) = &mut self.ui_state.popup
+						&& popup.update(popup_msg).is_some() {
+						return self.refresh_directory();
 					}
 					Command::none()
 				}
@@ -500,39 +1261,127 @@ impl FileManager {
 	pub fn navigate_to_path(&mut self, path: PathBuf) -> Command<Message> {
 		self.navigation.update_current_scroll(self.ui_state.scroll_offset);
 		self.navigation.navigate_to(path);
-		self.refresh_directory()
+		self.ui_state.clear_transient_state();
+		// The list keeps its scroll position across folders unless told otherwise
+		let scroll = self.scroll_list_to(0.0);
+		Command::batch([scroll, self.open_current_directory()])
 	}
 
+	/// Reads the current directory again, skipping the cache
 	pub fn refresh_directory(&mut self) -> Command<Message> {
 		self.ui_state.clear_transient_state();
-		self.files.clear();
+		self.load_current_directory()
+	}
+
+	/// Starts streaming the current directory, cancelling any load still running
+	fn load_current_directory(&mut self) -> Command<Message> {
 		self.ui_state.loading = true;
-		helper::load_files_sync(self.navigation.current_path.clone())
+		let (generation, cancel) = self.files.begin_load(self.navigation.current_path.clone());
+		helper::load_files(self.navigation.current_path.clone(), generation, cancel)
+	}
+
+	/// Shows the cached listing of the current directory right away if it is still valid
+	/// and checks it with a read in the background, otherwise loads it like usual
+	fn open_current_directory(&mut self) -> Command<Message> {
+		let path = self.navigation.current_path.clone();
+		let Some((generation, cancel)) = self.files.begin_cached_load(path.clone(), &self.navigation.sort_order()) else {
+			return self.load_current_directory();
+		};
+
+		self.ui_state.loading = false;
+		let scroll = self.scroll_list_to(self.navigation.get_current_scroll());
+		Command::batch([scroll, self.load_folder_sizes(), helper::load_files(path, generation, cancel)])
+	}
+
+	/// Scrolls the file list to a relative position, 0 is the top and 1 the bottom
+	fn scroll_list_to(&mut self, relative: f32) -> Command<Message> {
+		self.ui_state.scroll_offset = relative;
+		// The list does not report positions it was moved to by a command
+		self.ui_state.list_offset = relative * self.max_list_offset();
+		scrollable::snap_to(file_list_id(), RelativeOffset { x: 0.0, y: relative })
+	}
+
+	/// Scrolls the file list to a position in pixels
+	fn scroll_list_to_offset(&mut self, offset: f32) -> Command<Message> {
+		let max_offset = self.max_list_offset();
+		let offset = offset.clamp(0.0, max_offset);
+		self.ui_state.list_offset = offset;
+		self.ui_state.scroll_offset = if max_offset > 0.0 { offset / max_offset } else { 0.0 };
+		scrollable::scroll_to(file_list_id(), AbsoluteOffset { x: 0.0, y: offset })
 	}
 
-	fn copy_file_or_dir(&self, source: &PathBuf, dest: &PathBuf) -> Result<(), std::io::Error> {
-		if source.is_dir() {
-			copy_dir_all(source, dest)
+	/// How far the file list scrolls, the blank space below the rows included
+	fn max_list_offset(&self) -> f32 {
+		let rows = self.get_filtered_files().map_or(0, |files| files.len());
+		(rows as f32 * ROW_HEIGHT + LIST_BOTTOM_GAP - self.list_height()).max(0.0)
+	}
+
+	/// Height of the file list, until it reports one the whole window is assumed
+	fn list_height(&self) -> f32 {
+		if self.ui_state.list_height > 0.0 {
+			self.ui_state.list_height
 		} else {
-			fs::copy(source, dest).map(|_| ())
+			self.ui_state.window_size.height
 		}
 	}
 
+	/// Range of rows that need widgets, the visible ones plus a buffer on both sides
+	fn visible_rows(&self, rows: usize) -> std::ops::Range<usize> {
+		let height = self.list_height();
+		// The list may have shrunk since the last scroll event
+		let offset = self.ui_state.list_offset.min(self.max_list_offset());
+
+		let last = (((offset + height) / ROW_HEIGHT).ceil() as usize + ROW_BUFFER).min(rows);
+		let first = ((offset / ROW_HEIGHT) as usize).saturating_sub(ROW_BUFFER).min(last);
+		first..last
+	}
+
+	/// Calculates the sizes of the shown folders in the background when that is turned on
+	fn load_folder_sizes(&mut self) -> Command<Message> {
+		if !self.ui_state.folder_sizes || self.ui_state.loading {
+			return Command::none();
+		}
+		let (dirs, generation, cancel) = self.files.begin_sizes();
+		// Cached sizes were filled in, they may change the order
+		self.files.sort(&self.navigation.sort_order());
+		helper::load_folder_sizes(dirs, generation, cancel)
+	}
+
 	// Subscription helpers
 	fn keyboard_subscription(&self) -> Subscription<Message> {
 		keyboard::on_key_press(|key, modifiers| {
+			use keyboard::key::Named;
 			match key {
 				keyboard::Key::Character(c) if modifiers.command() => match c.as_str() {
 					"c" => Some(Message::CopySelected),
 					"x" => Some(Message::CutSelected),
 					"v" => Some(Message::PasteSelected),
+					"a" | "A" if modifiers.shift() => Some(Message::ClearSelection),
+					"a" => Some(Message::SelectAll),
+					"l" | "L" => Some(Message::FocusPathBar),
+					_ => None,
+				},
+				keyboard::Key::Character(c) if !modifiers.alt() => Some(Message::TypeAhead(c.to_string())),
+				keyboard::Key::Named(named_key) if modifiers.alt() => match named_key {
+					Named::ArrowLeft => Some(Message::NavigateBack),
+					Named::ArrowRight => Some(Message::NavigateForward),
+					Named::ArrowUp => Some(Message::NavigateUp),
 					_ => None,
 				},
 				keyboard::Key::Named(named_key) => match named_key {
-					keyboard::key::Named::Backspace => Some(Message::BackspacePressed),
-					keyboard::key::Named::F2 => Some(Message::PopupMessage(PopupMessage::StartRename)),
-					keyboard::key::Named::Escape => Some(Message::PopupMessage(PopupMessage::ClosePopup)),
-					keyboard::key::Named::F5 => Some(Message::Refresh),
+					Named::Backspace => Some(Message::BackspacePressed),
+					Named::F2 => Some(Message::PopupMessage(PopupMessage::StartRename)),
+					Named::Escape => Some(Message::EscapePressed),
+					Named::F5 => Some(Message::Refresh),
+					Named::Delete => Some(Message::DeleteSelected),
+					Named::Enter => Some(Message::OpenFocused),
+					Named::Space => Some(Message::TypeAhead(" ".to_string())),
+					Named::ArrowUp => Some(Message::MoveFocus(FocusMove::Up)),
+					Named::ArrowDown => Some(Message::MoveFocus(FocusMove::Down)),
+					Named::PageUp => Some(Message::MoveFocus(FocusMove::PageUp)),
+					Named::PageDown => Some(Message::MoveFocus(FocusMove::PageDown)),
+					Named::Home => Some(Message::MoveFocus(FocusMove::Home)),
+					Named::End => Some(Message::MoveFocus(FocusMove::End)),
 					_ => None,
 				},
 				_ => None,
@@ -548,6 +1397,12 @@ impl FileManager {
 			Event::Mouse(mouse::Event::ButtonPressed(button)) => {
 				Some(Message::MouseButtonPressed(button))
 			}
+			Event::Mouse(mouse::Event::ButtonReleased(button)) => {
+				Some(Message::MouseButtonReleased(button))
+			}
+			Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+				Some(Message::ModifiersChanged(modifiers))
+			}
 			Event::Window(_id, iced::window::Event::Resized { width, height }) => {
 				Some(Message::WindowResized(Size::new(width as f32, height as f32)))
 			}
@@ -556,8 +1411,9 @@ impl FileManager {
 	}
 
 	// View methods (kept similar but organized better)
-	fn view_control_panel(&self) -> Element<Message> {
+	fn view_control_panel(&self) -> Element<'_, Message> {
 		let path_input = text_input("Directory path", &self.navigation.path_input)
+			.id(path_input_id())
 			.on_input(Message::PathInputChanged)
 			.on_submit(Message::PathSubmitted)
 			.padding(8)
@@ -569,13 +1425,36 @@ impl FileManager {
 			.align_items(Alignment::Center);
 
 		let nav_buttons = self.create_navigation_buttons();
-		let hidden_checkbox = checkbox("Show hidden", self.ui_state.show_hidden)
-			.on_toggle(|_| Message::ToggleHidden);
 
-		let nav_row = row![nav_buttons, hidden_checkbox]
+		let selection = &self.ui_state.selection;
+		let loading_text = if self.ui_state.loading {
+			format!("Loading... {} items", self.files.len())
+		} else if !selection.is_empty() {
+			format!("{} selected ({})", selection.len(), format_size(selection.total_size()))
+		} else {
+			String::new()
+		};
+		let loading_status = text(loading_text)
+			.style(Color(iced::Color::from_rgb(0.7, 0.7, 0.8)))
+			.width(Length::Fill);
+
+		let stats = self.files.stats();
+		let cache_status = text(format!(
+			"Cache: {} folders, {}, {} hits, {} misses",
+			stats.entries,
+			format_size(stats.bytes as u64),
+			stats.hits,
+			stats.misses,
+		))
+		.size(12)
+		.style(Color(iced::Color::from_rgb(0.5, 0.5, 0.6)));
+
+		let nav_row = row![nav_buttons, loading_status, cache_status]
 			.spacing(8)
 			.align_items(Alignment::Center);
 
+		let options_row = self.view_list_options();
+
 		let error_or_headers = if let Some(err) = &self.ui_state.error_message {
 			text(err)
 				.style(Color(iced::Color::from_rgb8(255, 100, 100)))
@@ -584,13 +1463,58 @@ impl FileManager {
 			self.view_table_headers()
 		};
 
-		column![path_row, nav_row, error_or_headers]
+		column![path_row, nav_row, options_row, error_or_headers]
 			.spacing(8)
 			.padding(8)
 			.into()
 	}
 
-	fn create_navigation_buttons(&self) -> Element<Message> {
+	fn view_list_options(&self) -> Element<'_, Message> {
+		let label_color = iced::Color::from_rgb(0.7, 0.7, 0.8);
+		let order = self.navigation.sort_order();
+
+		let hidden_checkbox = checkbox("Show hidden", self.ui_state.show_hidden)
+			.on_toggle(|_| Message::ToggleHidden);
+		let folders_first_checkbox = checkbox("Folders first", order.folders_first)
+			.on_toggle(|_| Message::ToggleFoldersFirst);
+		let folder_sizes_checkbox = checkbox("Folder sizes", self.ui_state.folder_sizes)
+			.on_toggle(|_| Message::ToggleFolderSizes);
+		let natural_checkbox = checkbox("Natural order", order.collation == Collation::Natural)
+			.on_toggle(|_| Message::ToggleNaturalOrder);
+		let verify_checkbox = checkbox("Verify copies", self.ui_state.verify_copies)
+			.on_toggle(|_| Message::ToggleVerifyCopies);
+
+		let secondary_list = pick_list(
+			&SortColumn::ALL[..],
+			Some(order.secondary),
+			Message::SecondarySortChanged,
+		)
+		.padding(6);
+
+		let time_style_list = pick_list(
+			&TimeStyle::ALL[..],
+			Some(self.ui_state.time_style),
+			Message::TimeStyleChanged,
+		)
+		.padding(6);
+
+		row![
+			hidden_checkbox,
+			folder_sizes_checkbox,
+			folders_first_checkbox,
+			natural_checkbox,
+			text("Then by").style(Color(label_color)),
+			secondary_list,
+			text("Dates").style(Color(label_color)),
+			time_style_list,
+			verify_checkbox,
+		]
+		.spacing(8)
+		.align_items(Alignment::Center)
+		.into()
+	}
+
+	fn create_navigation_buttons(&self) -> Element<'_, Message> {
 		let delete_button = self.create_delete_button();
 		let up_button = button("Up").on_press(Message::NavigateUp).padding(8);
 		let home_button = button("Home").on_press(Message::NavigateHome).padding(8);
@@ -603,7 +1527,7 @@ impl FileManager {
 			.into()
 	}
 
-	fn create_delete_button(&self) -> Element<Message> {
+	fn create_delete_button(&self) -> Element<'_, Message> {
 		button(text("Delete").style(Color(iced::Color::from_rgb(0.9, 0.9, 0.9))))
 			.style(iced::theme::Button::Destructive)
 			.padding(8)
@@ -611,7 +1535,7 @@ impl FileManager {
 			.into() // Add .into() to convert Button to Element
 	}
 
-	fn create_history_buttons(&self) -> (Element<Message>, Element<Message>) {
+	fn create_history_buttons(&self) -> (Element<'_, Message>, Element<'_, Message>) {
 		let back_button = button("<")
 			.on_press_maybe(self.navigation.can_go_back().then_some(Message::NavigateBack))
 			.padding(8)
@@ -635,29 +1559,58 @@ impl FileManager {
 		(back_button, forward_button)
 	}
 
-	fn view_table_headers(&self) -> Element<Message> {
-		let header_color = iced::Color::from_rgb(0.6, 0.6, 0.7);
-
-		let name_header = text("Name")
-			.style(Color(header_color))
-			.width(Length::FillPortion(self.ui_state.columns.name() as u16));
-		let date_header = text("Modified")
-			.style(Color(header_color))
-			.width(Length::FillPortion(self.ui_state.columns.date() as u16))
-			.horizontal_alignment(alignment::Horizontal::Center);
-		let size_header = text("Size")
-			.style(Color(header_color))
-			.width(Length::FillPortion(self.ui_state.columns.size() as u16))
-			.horizontal_alignment(alignment::Horizontal::Right);
+	fn view_table_headers(&self) -> Element<'_, Message> {
+		let columns = &self.ui_state.columns;
+		let name_header = self.create_header_button(SortColumn::Name, columns.name(), alignment::Horizontal::Left);
+		let type_header = self.create_header_button(SortColumn::Type, columns.kind(), alignment::Horizontal::Left);
+		let date_header = self.create_header_button(SortColumn::Modified, columns.date(), alignment::Horizontal::Center);
+		let size_header = self.create_header_button(SortColumn::Size, columns.size(), alignment::Horizontal::Right);
+
+		let mut headers = row![name_header, type_header, date_header, size_header];
+		let warnings = self.files.warnings();
+		if warnings > 0 {
+			let label = text(format!("{} unreadable", warnings))
+				.style(Color(iced::Color::from_rgb(1.0, 0.6, 0.3)));
+			// What went wrong with the last entry that could not be listed, on hover
+			let label: Element<'_, Message> = match &self.files.last_warning {
+				Some(warning) => tooltip(label, text(warning).size(12), tooltip::Position::Bottom)
+					.style(iced::theme::Container::Box)
+					.into(),
+				None => label.into(),
+			};
+			headers = headers.push(label);
+		}
 
-		row![name_header, date_header, size_header]
+		headers
 			.spacing(8)
 			.width(Length::Fill)
 			.into()
 	}
 
-	fn view_file_list(&self) -> Element<Message> {
-		if self.ui_state.loading {
+	/// Header that sorts by its column when clicked, the active one shows the direction
+	fn create_header_button(&self, column: SortColumn, width: f32, align: alignment::Horizontal) -> Element<'_, Message> {
+		let order = self.navigation.sort_order();
+		let label = match (order.column == column, order.descending) {
+			(true, false) => format!("{} ▲", column),
+			(true, true) => format!("{} ▼", column),
+			(false, _) => column.to_string(),
+		};
+
+		button(
+			text(label)
+				.style(Color(iced::Color::from_rgb(0.6, 0.6, 0.7)))
+				.width(Length::Fill)
+				.horizontal_alignment(align)
+		)
+		.on_press(Message::SortBy(column))
+		.style(iced::theme::Button::Text)
+		.padding(0)
+		.width(Length::FillPortion(width as u16))
+		.into()
+	}
+
+	fn view_file_list(&self) -> Element<'_, Message> {
+		if self.ui_state.loading && self.files.len() == 0 {
 			return self.create_loading_view();
 		}
 
@@ -668,7 +1621,7 @@ impl FileManager {
 		}
 	}
 
-	fn create_loading_view(&self) -> Element<Message> {
+	fn create_loading_view(&self) -> Element<'_, Message> {
 		container(
 			text("Loading...")
 				.style(Color(iced::Color::from_rgb(0.7, 0.7, 0.8)))
@@ -681,7 +1634,7 @@ impl FileManager {
 		.into()
 	}
 
-	fn create_error_view(&self) -> Element<Message> {
+	fn create_error_view(&self) -> Element<'_, Message> {
 		container(
 			text(format!(
 				"Could not read directory contents: {}",
@@ -696,24 +1649,32 @@ impl FileManager {
 		.into()
 	}
 
-	fn get_filtered_files(&self) -> Option<Vec<&FileEntry>> {
-		self.files.get_files().map(|files| {
-			if self.ui_state.show_hidden {
-				files.iter().collect()
-			} else {
-				files.iter().filter(|f| !f.is_hidden()).collect()
-			}
-		})
+	fn get_filtered_files(&self) -> Option<Shown<'_>> {
+		self.files.get_files().map(|_| self.files.visible())
 	}
 
-	fn create_file_list_view(&self, files: Vec<&FileEntry>) -> Element<Message> {
-		let file_rows = Column::with_children(
-			files.into_iter().map(|file| self.view_file_row(file.clone()))
-		)
-		.spacing(4)
-		.width(Length::Fill);
+	/// Only the rows around the viewport are built, empty space stands in for the rest
+	fn create_file_list_view(&self, files: Shown<'_>) -> Element<'_, Message> {
+		let visible = self.visible_rows(files.len());
+		let above = visible.start as f32 * ROW_HEIGHT;
+		let below = (files.len() - visible.end) as f32 * ROW_HEIGHT;
+
+		// Blank space below the rows, at least enough to fill the list
+		let gap = (self.list_height() - files.len() as f32 * ROW_HEIGHT).max(LIST_BOTTOM_GAP);
+
+		let file_rows = Column::new()
+			.push(Space::with_height(above))
+			.extend(files.range(visible).map(|file| self.view_file_row(file)))
+			.push(Space::with_height(below + gap))
+			.padding([0, LIST_GUTTER])
+			.width(Length::Fill);
+		// Rows handle their own presses, the ones on blank space start a rubber band
+		let file_rows = mouse_area(file_rows)
+			.on_press(Message::ListPressed)
+			.on_move(Message::ListPointerMoved);
 
 		let scrollable_content = scrollable(file_rows)
+			.id(file_list_id())
 			.width(Length::Fill)
 			.height(Length::Fill)
 			.on_scroll(Message::ScrollChanged);
@@ -725,18 +1686,24 @@ impl FileManager {
 			.into()
 	}
 
-	fn view_file_row(&self, file: FileEntry) -> Element<Message> {
-		let is_selected = self.ui_state.selected_file.as_ref() == Some(&file.path());
-		let (prefix, text_color) = get_file_display_info(&file);
+	fn view_file_row(&self, file: &FileEntry) -> Element<'_, Message> {
+		let is_selected = self.ui_state.selection.contains(file.as_path());
+		let (prefix, text_color) = get_file_display_info(file);
 
-		let name_text = if file.is_dir() || file.is_shortcut() {
+		let name_text = if let Some(error) = file.error() {
+			format!("{} {} ({})", prefix, file.display_name(), error)
+		} else if let Some(link) = file.link() {
+			format!("{} {} -> {}", prefix, file.display_name(), link.target.display())
+		} else if !prefix.is_empty() {
 			format!("{} {}", prefix, file.display_name())
 		} else {
-			file.display_name().clone()
+			file.display_name().to_string()
 		};
 
-		let row_content = self.create_file_row_content(name_text, text_color, &file);
-		let container_style = if is_selected {
+		let row_content = self.create_file_row_content(name_text, text_color, file);
+		let container_style = if self.ui_state.focused.as_deref() == Some(file.as_path()) {
+			iced::theme::Container::Custom(Box::new(FocusedRowStyle { selected: is_selected }))
+		} else if is_selected {
 			iced::theme::Container::Box
 		} else {
 			iced::theme::Container::Transparent
@@ -746,7 +1713,10 @@ impl FileManager {
 		let content_container = container(row_content)
 			.style(container_style)
 			.padding(4)
-			.width(Length::Fill);
+			.width(Length::Fill)
+			.height(ROW_HEIGHT)
+			.center_y()
+			.clip(true);
 
 		mouse_area(content_container)
 			.on_press(Message::FileLeftClicked(file_path.clone()))
@@ -756,22 +1726,39 @@ impl FileManager {
 			.into()
 	}
 
-	fn create_file_row_content(&self, name_text: String, text_color: iced::Color, file: &FileEntry) -> Element<Message> {
+	fn create_file_row_content(&self, name_text: String, text_color: iced::Color, file: &FileEntry) -> Element<'_, Message> {
 		let name = text(name_text)
 			.style(Color(text_color))
 			.width(Length::FillPortion(self.ui_state.columns.name() as u16));
 
-		let modified = text(&file.modified())
+		let kind = text(helper::type_text(file))
 			.style(Color(iced::Color::from_rgb(0.6, 0.6, 0.7)))
-			.width(Length::FillPortion(self.ui_state.columns.date() as u16))
+			.width(Length::FillPortion(self.ui_state.columns.kind() as u16));
+
+		let modified_text = text(helper::modified_text(file, self.ui_state.time_style))
+			.style(Color(iced::Color::from_rgb(0.6, 0.6, 0.7)))
+			.width(Length::Fill)
 			.horizontal_alignment(alignment::Horizontal::Center);
+		// Full timestamp on hover
+		let modified: Element<'_, Message> = match file.modified() {
+			Some(time) => tooltip(
+				modified_text,
+				text(format_time_full(time)).size(12),
+				tooltip::Position::Bottom,
+			)
+			.style(iced::theme::Container::Box)
+			.into(),
+			None => modified_text.into(),
+		};
+		let modified = container(modified)
+			.width(Length::FillPortion(self.ui_state.columns.date() as u16));
 
-		let size = text(&file.size())
+		let size = text(helper::size_text(file, self.ui_state.folder_sizes))
 			.style(Color(iced::Color::from_rgb(0.6, 0.6, 0.7)))
 			.width(Length::FillPortion(self.ui_state.columns.size() as u16))
 			.horizontal_alignment(alignment::Horizontal::Right);
 
-		row![name, modified, size]
+		row![name, kind, modified, size]
 			.spacing(8)
 			.width(Length::Fill)
 			.align_items(Alignment::Center)
@@ -784,19 +1771,32 @@ impl Clone for FileManager {
 		Self {
 			navigation: self.navigation.clone(),
 			ui_state: UIState {
-				selected_file: self.ui_state.selected_file.clone(),
+				selection: self.ui_state.selection.clone(),
+				modifiers: self.ui_state.modifiers,
+				list_pointer: None,
+				lasso: None,
+				focused: self.ui_state.focused.clone(),
+				type_ahead: None,
 				hovered_file: None, // Don't clone transient hover state
 				error_message: self.ui_state.error_message.clone(),
+				watch_error: self.ui_state.watch_error.clone(),
 				show_hidden: self.ui_state.show_hidden,
+				folder_sizes: self.ui_state.folder_sizes,
+				time_style: self.ui_state.time_style,
+				verify_copies: self.ui_state.verify_copies,
 				columns: Columns::new(), // Recreate columns
 				scroll_offset: self.ui_state.scroll_offset,
+				list_offset: self.ui_state.list_offset,
+				list_height: self.ui_state.list_height,
 				popup: None, // Don't clone popup state
+				conflict: self.ui_state.conflict.clone(),
 				mouse_position: Point::ORIGIN, // Reset mouse position
 				loading: self.ui_state.loading,
 				window_size: self.ui_state.window_size,
 			},
 			clipboard: self.clipboard.clone(),
 			files: self.files.clone(),
+			jobs: self.jobs.clone(),
 		}
 	}
 }
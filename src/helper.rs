@@ -1,29 +1,41 @@
 
-use super::*;
 use super::file_manager::Message;
-use std::{io, fs, path::Path, path::PathBuf, time::SystemTime, os::windows::fs::MetadataExt};
+use super::jobs::JobMessage;
+use iced::futures::{channel::mpsc, SinkExt, StreamExt};
+use rsfiles_core::{
+	directory_size, format_size, format_time_styled, stream_directory_contents, watch_directory, CancelToken, FileEntry,
+	CopyJournal, Job, JobControl, JobProgress, LinkState, TimeStyle,
+};
+use std::{path::PathBuf, thread, time::Duration};
 
+// Entries in the first streamed batch, later batches grow from here
+const FIRST_BATCH_SIZE: usize = 256;
+// Quiet time before collected file system changes are applied to the listing
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+// Interval of the list scrolling while a rubber band is dragged at its edge
+const AUTO_SCROLL_INTERVAL: Duration = Duration::from_millis(30);
 
-// Add this for Windows shortcut resolution
-#[cfg(windows)]
-use winapi::{
-	Interface,
-	um::{
-		objbase::CoInitialize,
-		combaseapi::{CoCreateInstance, CoUninitialize},
-		shobjidl_core::{IShellLinkW},
-		objidl::IPersistFile
-	},
-	shared::{
-		wtypesbase::CLSCTX_INPROC_SERVER,
-		winerror::{S_OK, S_FALSE},
-		guiddef::CLSID
-	}
-};
+/// Progress of a directory load, tagged with its generation in `Message::FilesLoaded`
+#[derive(Debug, Clone)]
+pub enum LoadEvent {
+	Batch(Vec<FileEntry>),
+	/// An entry could not be listed at all, the rest keeps loading
+	Warning(String),
+	Finished,
+	Failed(String),
+}
 
 
 pub fn get_file_display_info(file: &FileEntry) -> (&'static str, iced::Color) {
-	if file.is_dir() {
+	if file.is_unreadable() {
+		("[ERR]", iced::Color::from_rgb(1.0, 0.6, 0.3))
+	} else if let Some(link) = file.link() {
+		match link.state {
+			LinkState::Ok => ("[SYM]", iced::Color::from_rgb(0.5, 0.9, 0.8)),
+			LinkState::Broken => ("[BRK]", iced::Color::from_rgb(1.0, 0.45, 0.45)),
+			LinkState::Loop => ("[LOOP]", iced::Color::from_rgb(1.0, 0.45, 0.45)),
+		}
+	} else if file.is_dir() {
 		("[DIR]", iced::Color::from_rgb(0.5, 0.7, 1.0))
 	} else if file.is_shortcut() {
 		("[LNK]", iced::Color::from_rgb(1.0, 0.8, 0.5))
@@ -32,111 +44,53 @@ pub fn get_file_display_info(file: &FileEntry) -> (&'static str, iced::Color) {
 	}
 }
 
-#[cfg(windows)]
-const STGM_READ: u32 = 0x00000000;
-
-// Define CLSID_ShellLink
-#[cfg(windows)]
-const CLSID_SHELLLINK: CLSID = CLSID {
-	Data1: 0x00021401,
-	Data2: 0x0000,
-	Data3: 0x0000,
-	Data4: [0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46],
-};
-
+/// Text of the Modified column
+pub fn modified_text(file: &FileEntry, style: TimeStyle) -> String {
+	if file.is_unreadable() {
+		return String::new();
+	}
+	file.modified()
+		.map(|time| format_time_styled(time, style))
+		.unwrap_or_else(|| "Unknown".to_string())
+}
 
-// Function to resolve Windows shortcut (.lnk) files
-#[cfg(windows)]
-pub fn resolve_shortcut(lnk_path: &PathBuf) -> Option<PathBuf> {
-	use std::ptr;
-	use std::ffi::OsStr;
-	use std::os::windows::ffi::OsStrExt;
-	
-	unsafe {
-		// Initialize COM
-		let hr = CoInitialize(ptr::null_mut());
-		if hr != S_OK && hr != S_FALSE { // S_FALSE means already initialized
-			return None;
-		}
-		
-		let mut shell_link: *mut IShellLinkW = ptr::null_mut();
-		let hr = CoCreateInstance(
-			&CLSID_SHELLLINK,
-			ptr::null_mut(),
-			CLSCTX_INPROC_SERVER,
-			&IShellLinkW::uuidof(),
-			&mut shell_link as *mut *mut IShellLinkW as *mut *mut _,
-		);
-		
-		if hr != S_OK || shell_link.is_null() {
-			CoUninitialize();
-			return None;
-		}
-		
-		// Query for IPersistFile interface
-		let mut persist_file: *mut IPersistFile = ptr::null_mut();
-		let hr = (*shell_link).QueryInterface(
-			&IPersistFile::uuidof(),
-			&mut persist_file as *mut *mut IPersistFile as *mut *mut _,
-		);
-		
-		if hr != S_OK || persist_file.is_null() {
-			(*shell_link).Release();
-			CoUninitialize();
-			return None;
-		}
-		
-		// Convert path to wide string
-		let wide_path: Vec<u16> = OsStr::new(lnk_path)
-			.encode_wide()
-			.chain(std::iter::once(0))
-			.collect();
-		
-		// Load the shortcut
-		let hr = (*persist_file).Load(wide_path.as_ptr(), STGM_READ);
-		if hr != S_OK {
-			(*persist_file).Release();
-			(*shell_link).Release();
-			CoUninitialize();
-			return None;
-		}
-		
-		// Resolve the link (this is important for shortcuts to folders)
-		let _ = (*shell_link).Resolve(ptr::null_mut(), 0);
-		
-		// Get the target path
-		let mut target_path = [0u16; winapi::shared::minwindef::MAX_PATH as usize];
-		let hr = (*shell_link).GetPath(target_path.as_mut_ptr(), target_path.len() as i32, ptr::null_mut(), STGM_READ);
-		
-		// Cleanup
-		(*persist_file).Release();
-		(*shell_link).Release();
-		CoUninitialize();
-		
-		if hr == S_OK {
-			// Convert wide string back to PathBuf
-			let len = target_path.iter().position(|&x| x == 0).unwrap_or(0);
-			if len > 0 {
-				let target_string = String::from_utf16_lossy(&target_path[..len]);
-				Some(PathBuf::from(target_string))
-			} else {
-				None
-			}
-		} else {
-			None
+/// Text of the Size column, links that lead nowhere have none and folders only
+/// once their size is calculated ("..." while it is still running)
+pub fn size_text(file: &FileEntry, folder_sizes: bool) -> String {
+	let dangling = file.link().is_some_and(|link| link.state != LinkState::Ok);
+	if file.is_dir() {
+		match file.dir_size() {
+			Some(size) if size.is_partial() => format!("{}+", format_size(size.bytes)),
+			Some(size) => format_size(size.bytes),
+			None if folder_sizes && !file.is_symlink() && !file.is_unreadable() => "...".to_string(),
+			None => String::new(),
 		}
+	} else if file.is_unreadable() || dangling {
+		String::new()
+	} else {
+		format_size(file.size())
 	}
 }
 
-// For non-Windows platforms, return None
-#[cfg(not(windows))]
-pub fn resolve_shortcut(_lnk_path: &PathBuf) -> Option<PathBuf> {
-	None
+/// Text of the Type column
+pub fn type_text(file: &FileEntry) -> String {
+	if file.is_dir() {
+		"Folder".to_string()
+	} else if file.is_symlink() {
+		"Link".to_string()
+	} else if file.is_shortcut() {
+		"Shortcut".to_string()
+	} else if file.extension().is_empty() {
+		"File".to_string()
+	} else {
+		format!("{} file", file.extension().to_uppercase())
+	}
 }
 
 #[derive(Clone)]
 pub struct Columns {
 	name: f32,
+	kind: f32,
 	date: f32,
 	size: f32,
 }
@@ -144,247 +98,157 @@ pub struct Columns {
 impl Columns {
 	pub fn new() -> Self { Self {
 		name: 50.0,
+		kind: 15.0,
 		date: 25.0,
 		size: 20.0,
 	}}
 
 	pub fn name(&self) -> f32 { self.name }
+	pub fn kind(&self) -> f32 { self.kind }
 	pub fn date(&self) -> f32 { self.date }
 	pub fn size(&self) -> f32 { self.size }
 }
 
-#[derive(Clone, Debug)]
-pub struct FileEntry {
-	path: PathBuf,
-	display_name: String,
-	is_dir: bool,
-	modified: String,
-	size: String,
-	is_hidden: bool,
-}
-#[allow(dead_code)]
-impl FileEntry {
-	pub fn new(
-		path: PathBuf,
-		display_name: String,
-		is_dir: bool,
-		modified: String,
-		size: String,
-		is_hidden: bool,
-	) -> Self {
-		Self{
-			path,
-			display_name,
-			is_dir,
-			modified,
-			size,
-			is_hidden,
-		}
-	}
-
-	pub fn path(&self) -> PathBuf { self.path.clone() }
-	pub fn display_name(&self) -> String { self.display_name.clone() }
-	pub fn is_dir(&self) -> bool { self.is_dir }
-	pub fn is_shortcut(&self) -> bool { self.path.is_shortcut() }
-	pub fn modified(&self) -> String { self.modified.clone() }
-	pub fn size(&self) -> String { self.size.clone() }
-	pub fn is_hidden(&self) -> bool { self.is_hidden }
-	pub fn extension(&self) -> String { 
-	self.path
-		.extension()
-		.and_then(|ext| ext.to_str())
-		.unwrap_or_default()
-		.to_lowercase()
-		.to_string()
-	}
+/// Row the keyboard cursor is on, outlined and filled like the others when selected
+pub struct FocusedRowStyle {
+	pub selected: bool,
 }
 
-pub trait PathExt {
-	fn is_shortcut(&self) -> bool;
-}
+impl iced::widget::container::StyleSheet for FocusedRowStyle {
+	type Style = iced::Theme;
 
-impl PathExt for PathBuf {
-	fn is_shortcut(&self) -> bool {
-		self.extension()
-			.and_then(|ext| ext.to_str())
-			.map(|ext| ext.eq_ignore_ascii_case("lnk"))
-			.unwrap_or(false)
+	fn appearance(&self, style: &Self::Style) -> iced::widget::container::Appearance {
+		let palette = style.extended_palette();
+		iced::widget::container::Appearance {
+			background: self.selected.then(|| palette.background.weak.color.into()),
+			border: iced::Border {
+				color: palette.primary.strong.color,
+				width: 1.0,
+				radius: 2.0.into(),
+			},
+			shadow: iced::Shadow::default(),
+			text_color: None,
+		}
 	}
 }
 
+/// Lists the directory on a background thread and streams the entries back in batches.
+/// The thread stops as soon as `cancel` is set or nobody listens anymore.
+pub fn load_files(path: PathBuf, generation: u64, cancel: CancelToken) -> iced::Command<Message> {
+	let (sender, receiver) = mpsc::unbounded();
 
+	thread::spawn(move || {
+		let batches = match stream_directory_contents(&path, FIRST_BATCH_SIZE, cancel.clone()) {
+			Ok(batches) => batches,
+			Err(e) => {
+				let _ = sender.unbounded_send(LoadEvent::Failed(e.to_string()));
+				return;
+			}
+		};
 
-pub fn format_size(bytes: u64) -> String {
-	const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
-	const UNIT_SIZE: f64 = 1024.0;
-
-	if bytes == 0 {
-		return "0 B".to_string();
-	}
+		for batch in batches {
+			let event = match batch {
+				Ok(files) => LoadEvent::Batch(files),
+				Err(e) => LoadEvent::Warning(e.to_string()),
+			};
+			if sender.unbounded_send(event).is_err() {
+				return;
+			}
+		}
 
-	let i = (bytes as f64).log(UNIT_SIZE).floor() as usize;
-	let size = bytes as f64 / UNIT_SIZE.powi(i as i32);
+		if !cancel.is_cancelled() {
+			let _ = sender.unbounded_send(LoadEvent::Finished);
+		}
+	});
 
-	if i < UNITS.len() {
-		format!("{:.1} {}", size, UNITS[i])
-	} else {
-		let tb_size = bytes as f64 / UNIT_SIZE.powi(4);
-		format!("{:.1} TB", tb_size)
-	}
+	iced::Command::run(receiver, move |event| Message::FilesLoaded(generation, event))
 }
 
-#[allow(dead_code)]
-pub fn format_time_ago(time: SystemTime) -> String {
-	let now = SystemTime::now();
-	let duration = now.duration_since(time).unwrap_or_default();
-	let secs = duration.as_secs();
+/// Adds up the folders one after another on a background thread, every result is sent
+/// back as soon as it is known. Stops at the next folder or file once `cancel` is set.
+pub fn load_folder_sizes(dirs: Vec<PathBuf>, generation: u64, cancel: CancelToken) -> iced::Command<Message> {
+	let (sender, receiver) = mpsc::unbounded();
 
-	let years = secs / 31_536_000;
-	let days = (secs % 31_536_000) / 86400;
-	let hours = (secs % 86400) / 3600;
-	let minutes = (secs % 3600) / 60;
+	thread::spawn(move || {
+		for dir in dirs {
+			let Some(size) = directory_size(&dir, &cancel) else { return };
+			if sender.unbounded_send((dir, size)).is_err() {
+				return;
+			}
+		}
+	});
 
-	if years > 0 {
-		format!("{}y {}d ago", years, days)
-	} else if days > 0 {
-		format!("{}d {}h ago", days, hours)
-	} else if hours > 0 {
-		format!("{}h {}m ago", hours, minutes)
-	} else if minutes > 0 {
-		format!("{}m ago", minutes)
-	} else {
-		"Just now".to_string()
-	}
+	iced::Command::run(receiver, move |(path, size)| Message::FolderSizeLoaded(generation, path, size))
 }
-pub fn format_time(time: SystemTime) -> String {
-	match time.duration_since(SystemTime::UNIX_EPOCH) {
-		Ok(duration) => {
-			let secs = duration.as_secs();
-			let minutes = secs / 60;
-			let hours = minutes / 60;
-			let days = hours / 24;
-			
-			// This is a simplified calculation - for precise date/time you'd need to handle
-			// leap years, months with different days, etc. (which is why chrono is better)
-			let year = 1970 + (days / 365) as i32;
-			let month = ((days % 365) / 30 + 1) as u32;
-			let day = (days % 30 + 1) as u32;
-			let hour = (hours % 24) as u32;
-			let minute = (minutes % 60) as u32;
-			
-			format!("{:04}.{:02}.{:02} {:02}:{:02}", year, month, day, hour, minute)
+
+/// Reports changes inside `path` for as long as the subscription is alive,
+/// iced drops it (and with it the watcher) once another folder is opened
+pub fn watch_files(path: PathBuf) -> iced::Subscription<Message> {
+	iced::subscription::channel(path.clone(), 16, move |mut output| async move {
+		let (sender, mut receiver) = mpsc::unbounded();
+		let watcher = watch_directory(&path, WATCH_DEBOUNCE, move |batch| sender.unbounded_send(batch).is_ok());
+		if let Err(e) = &watcher {
+			let _ = output.send(Message::WatchFailed(path.clone(), e.to_string())).await;
 		}
-		Err(_) => "Invalid time".to_string(),
-	}
-}
 
-pub fn copy_dir_all(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()> {
-	fs::create_dir_all(&dst)?;
-	for entry in fs::read_dir(src)? {
-		let entry = entry?;
-		let ty = entry.file_type()?;
-		if ty.is_dir() {
-			copy_dir_all(entry.path(), dst.as_ref().join(entry.file_name()))?;
-		} else {
-			fs::copy(entry.path(), dst.as_ref().join(entry.file_name()))?;
+		while let Some(batch) = receiver.next().await {
+			if output.send(Message::DirectoryChanged(path.clone(), batch)).await.is_err() {
+				break;
+			}
 		}
-	}
-	Ok(())
-}
 
-// Synchronous file loading for better performance on small directories
-pub fn load_files_sync(path: PathBuf) -> iced::Command<Message> {
-	iced::Command::perform(
-		async move {
-			load_directory_contents(&path)
-		},
-		Message::FilesLoaded,
-	)
+		drop(watcher);
+		iced::futures::future::pending().await
+	})
 }
 
+/// Ticks every few milliseconds while it is subscribed, drives auto scrolling
+pub fn auto_scroll_ticks() -> iced::Subscription<Message> {
+	iced::subscription::channel("auto_scroll", 1, |mut output| async move {
+		let (sender, mut receiver) = mpsc::unbounded();
+		// Stops with the first tick nobody receives anymore
+		thread::spawn(move || {
+			while sender.unbounded_send(()).is_ok() {
+				thread::sleep(AUTO_SCROLL_INTERVAL);
+			}
+		});
 
-/// Loads directory contents with proper hidden file checking
-pub fn load_directory_contents(path: &PathBuf) -> Result<Vec<FileEntry>, String> {
-	let mut files = Vec::new();
-	
-	let entries = fs::read_dir(path)
-		.map_err(|e| format!("Error reading directory: {}", e))?;
-
-	for entry in entries {
-		let entry = entry.map_err(|e| format!("Error reading directory entry: {}", e))?;
-		let path = entry.path();
-		
-		let display_name = path
-			.file_name()
-			.unwrap_or_default()
-			.to_string_lossy()
-			.to_string();
-		
-		let metadata = entry.metadata()
-			.map_err(|e| format!("Error reading metadata for {}: {}", display_name, e))?;
-
-		let modified_str = metadata
-			.modified()
-			.map(helper::format_time)
-			.unwrap_or_else(|_| "Unknown".to_string());
-
-		let size_str = if metadata.is_dir() {
-			String::new()
-		} else {
-			helper::format_size(metadata.len())
-		};
-
-		let is_hidden = is_file_hidden(&entry)?;
-
-		files.push(FileEntry::new(
-			path,
-			display_name,
-			metadata.is_dir(),
-			modified_str,
-			size_str,
-			is_hidden,
-		));
-	}
-
-	// Sort the files using the separate sorting function
-	sort_directory_contents(&mut files);
-
-	Ok(files)
-}
-
-/// Sorts directory contents with the following priority:
-/// 1. Directories (sorted by name)
-/// 2. Shortcuts (sorted by name)
-/// 3. Other files (sorted by extension, then by name)
-fn sort_directory_contents(files: &mut [FileEntry]) {
-	files.sort_by_key(|f| {
-		( if f.is_dir() { 0 } else if f.is_shortcut() { 1 } else { 2 },
-		  f.extension(), f.display_name().to_lowercase())
-	});
+		while receiver.next().await.is_some() {
+			if output.send(Message::AutoScrollTick).await.is_err() {
+				break;
+			}
+		}
+		iced::futures::future::pending().await
+	})
+}
+
+/// Runs the job on a background thread once subscribed and reports back how far it got.
+/// Pausing and cancelling go through `control`, the thread ends with the job. With a
+/// `journal` path it writes down what it gets done, to go on from there after a cancel.
+pub fn run_job(id: u64, job: Job, journal: Option<PathBuf>, control: JobControl) -> iced::Subscription<Message> {
+	iced::subscription::channel(("job", id), 16, move |mut output| async move {
+		let (sender, mut receiver) = mpsc::unbounded();
+		thread::spawn(move || {
+			let on_progress = |progress: &JobProgress| {
+				let _ = sender.unbounded_send(JobMessage::Progress(progress.clone()));
+			};
+			// The journal is there already when the job ran before, a job that can not
+			// have one runs all the same
+			let journal = journal.and_then(|path| {
+				if path.exists() { CopyJournal::load(path) } else { CopyJournal::create(path, &job) }.ok()
+			});
+			let outcome = match journal {
+				Some(journal) => rsfiles_core::run_job_journaled(journal, &control, on_progress),
+				None => rsfiles_core::run_job(&job, &control, on_progress),
+			};
+			let _ = sender.unbounded_send(JobMessage::Finished(outcome));
+		});
+
+		while let Some(message) = receiver.next().await {
+			if output.send(Message::Job(id, message)).await.is_err() {
+				break;
+			}
+		}
+		iced::futures::future::pending().await
+	})
 }
-
-
-/// Proper cross-platform hidden file check
-fn is_file_hidden(entry: &fs::DirEntry) -> Result<bool, String> {
-	#[cfg(unix)]
-	{
-		// On Unix, check if filename starts with a dot
-		let name = entry.file_name();
-		Ok(name.to_string_lossy().starts_with('.'))
-	}
-	
-	#[cfg(windows)]
-	{
-		// On Windows, check the hidden file attribute
-		let metadata = entry.metadata()
-			.map_err(|e| format!("Error reading metadata: {}", e))?;
-		Ok(metadata.file_attributes() & 0x2 != 0)
-	}
-	
-	#[cfg(not(any(unix, windows)))]
-	{
-		// For other platforms, fall back to dot file check
-		let name = entry.file_name();
-		Ok(name.to_string_lossy().starts_with('.'))
-	}
-}
\ No newline at end of file
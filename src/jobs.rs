@@ -0,0 +1,305 @@
+use std::{fs, path::PathBuf, time::{Duration, SystemTime, UNIX_EPOCH}};
+use crate::conflict::ConflictDialog;
+use crate::file_manager::{summarize_errors, FileManager, Message};
+use crate::helper;
+use iced::{
+	widget::{button, column, container, progress_bar, row, text, Column},
+	theme::Text::Color,
+	Alignment, Command, Element, Length, Subscription,
+};
+use rsfiles_core::{
+	check_destination, find_conflicts, format_size, ConflictPolicy, CopyJournal, CopyOptions, Job, JobControl, JobOutcome, JobProgress,
+};
+
+// Failures listed under a finished job, the rest is only counted
+const MAX_SHOWN_FAILURES: usize = 5;
+
+#[derive(Debug, Clone)]
+pub enum JobMessage {
+	Progress(JobProgress),
+	Finished(JobOutcome),
+	Pause,
+	Resume,
+	Cancel,
+	/// Goes on with a cancelled or interrupted job from where its journal says it stopped
+	Continue,
+	Dismiss,
+}
+
+/// A job in the panel. It disappears once it is done, unless it was cancelled
+/// or some entries failed, then it stays until it is dismissed.
+#[derive(Clone)]
+struct JobEntry {
+	id: u64,
+	job: Job,
+	// Copies and moves write down what they got done here, a cancelled one can go on
+	journal: Option<PathBuf>,
+	control: JobControl,
+	progress: JobProgress,
+	outcome: Option<JobOutcome>,
+}
+
+impl JobEntry {
+	fn can_continue(&self) -> bool {
+		self.outcome.as_ref().is_some_and(|outcome| outcome.cancelled)
+			&& self.journal.as_ref().is_some_and(|journal| journal.exists())
+	}
+}
+
+#[derive(Clone, Default)]
+pub struct Jobs {
+	entries: Vec<JobEntry>,
+	next_id: u64,
+}
+
+impl Jobs {
+	/// Jobs that were still running when rsfiles last closed show up stopped,
+	/// ready to go on where they were
+	pub fn new() -> Self {
+		let mut jobs = Self::default();
+		let interrupted = journal_dir().map(|dir| CopyJournal::load_all(&dir)).unwrap_or_default();
+		for journal in interrupted {
+			jobs.next_id += 1;
+			jobs.entries.push(JobEntry {
+				id: jobs.next_id,
+				job: journal.job().clone(),
+				journal: Some(journal.path().to_path_buf()),
+				control: JobControl::new(),
+				progress: JobProgress::default(),
+				outcome: Some(JobOutcome { cancelled: true, ..JobOutcome::default() }),
+			});
+		}
+		jobs
+	}
+
+	fn start(&mut self, job: Job) {
+		self.next_id += 1;
+		let journal = match job {
+			Job::Copy { .. } | Job::Move { .. } => journal_dir().map(|dir| {
+				let started = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+				dir.join(format!("{}-{}.journal", started.as_millis(), self.next_id))
+			}),
+			Job::Delete { .. } => None,
+		};
+		self.entries.push(JobEntry {
+			id: self.next_id,
+			job,
+			journal,
+			control: JobControl::new(),
+			progress: JobProgress::default(),
+			outcome: None,
+		});
+	}
+
+	fn find(&mut self, id: u64) -> Option<&mut JobEntry> {
+		self.entries.iter_mut().find(|entry| entry.id == id)
+	}
+
+	/// Every job that has not ended yet runs for as long as it is subscribed
+	pub fn subscription(&self) -> Subscription<Message> {
+		Subscription::batch(self.entries.iter()
+			.filter(|entry| entry.outcome.is_none())
+			.map(|entry| helper::run_job(entry.id, entry.job.clone(), entry.journal.clone(), entry.control.clone())))
+	}
+}
+
+/// Where the journals of running jobs are kept
+fn journal_dir() -> Option<PathBuf> {
+	dirs::data_local_dir().map(|dir| dir.join("rsfiles").join("jobs"))
+}
+
+impl FileManager {
+	/// Queues `job`, it starts right away next to the ones already running
+	pub fn start_job(&mut self, job: Job) -> Command<Message> {
+		self.jobs.start(job);
+		Command::none()
+	}
+
+	/// Copies or moves `sources` into `dest`. When names are taken there the conflict
+	/// dialog asks what to do first, the job starts once everything is answered.
+	pub fn start_transfer(&mut self, sources: Vec<PathBuf>, dest: PathBuf, is_move: bool) -> Command<Message> {
+		if let Some(e) = sources.iter().find_map(|source| check_destination(source, &dest).err()) {
+			self.ui_state.set_error(e.to_string());
+			return Command::none();
+		}
+		let conflicts = find_conflicts(&sources, &dest);
+		if !conflicts.is_empty() {
+			self.ui_state.popup = None;
+			self.ui_state.conflict = Some(ConflictDialog::new(sources, dest, is_move, self.copy_options(), conflicts));
+			return Command::none();
+		}
+
+		let (conflicts, options) = (ConflictPolicy::new(), self.copy_options());
+		self.start_job(if is_move {
+			Job::Move { sources, dest, conflicts, options }
+		} else {
+			Job::Copy { sources, dest, conflicts, options }
+		})
+	}
+
+	/// What copies keep and check, as chosen in the list options
+	fn copy_options(&self) -> CopyOptions {
+		CopyOptions { verify: self.ui_state.verify_copies, ..CopyOptions::default() }
+	}
+
+	pub fn handle_job_message(&mut self, id: u64, message: JobMessage) -> Command<Message> {
+		let Some(entry) = self.jobs.find(id) else {
+			return Command::none();
+		};
+
+		match message {
+			JobMessage::Progress(progress) => entry.progress = progress,
+			JobMessage::Finished(outcome) => {
+				entry.progress = outcome.progress.clone();
+				if !outcome.failures.is_empty() {
+					let errors = outcome.failures.iter()
+						.map(|failure| format!("{}: {}", failure.path.display(), failure.message))
+						.collect();
+					self.ui_state.set_error(summarize_errors(errors));
+				}
+				if outcome.failures.is_empty() && !outcome.cancelled {
+					self.jobs.entries.retain(|entry| entry.id != id);
+				} else {
+					entry.outcome = Some(outcome);
+				}
+			}
+			JobMessage::Pause => entry.control.pause(),
+			JobMessage::Resume => entry.control.resume(),
+			JobMessage::Cancel => entry.control.cancel(),
+			JobMessage::Continue => {
+				entry.control = JobControl::new();
+				entry.progress = JobProgress::default();
+				entry.outcome = None;
+			}
+			JobMessage::Dismiss => {
+				// Nothing can go on with it anymore, files it only copied partly go too
+				if let Some(journal) = &entry.journal {
+					match CopyJournal::load(journal) {
+						Ok(journal) => {
+							let _ = journal.discard();
+						}
+						Err(_) => {
+							let _ = fs::remove_file(journal);
+						}
+					}
+				}
+				self.jobs.entries.retain(|entry| entry.id != id);
+			}
+		}
+		// What the job changed shows up through the folder watcher
+		Command::none()
+	}
+
+	/// Running and failed jobs below the file list, nothing when there are none
+	pub fn view_jobs(&self) -> Option<Element<'_, Message>> {
+		if self.jobs.entries.is_empty() {
+			return None;
+		}
+
+		let jobs = Column::with_children(self.jobs.entries.iter().map(view_job))
+			.spacing(8)
+			.width(Length::Fill);
+		Some(container(jobs)
+			.style(iced::theme::Container::Box)
+			.padding(8)
+			.width(Length::Fill)
+			.into())
+	}
+}
+
+fn view_job(entry: &JobEntry) -> Element<'_, Message> {
+	let job_button = |label, message| {
+		button(text(label).size(12)).on_press(Message::Job(entry.id, message)).padding([2, 8])
+	};
+	let buttons = match &entry.outcome {
+		Some(_) if entry.can_continue() => row![
+			job_button("Resume", JobMessage::Continue),
+			job_button("Dismiss", JobMessage::Dismiss),
+		],
+		Some(_) => row![job_button("Dismiss", JobMessage::Dismiss)],
+		None if entry.control.is_paused() => row![
+			job_button("Resume", JobMessage::Resume),
+			job_button("Cancel", JobMessage::Cancel),
+		],
+		None => row![
+			job_button("Pause", JobMessage::Pause),
+			job_button("Cancel", JobMessage::Cancel),
+		],
+	};
+
+	let header = row![text(entry.job.description()).width(Length::Fill), buttons.spacing(4)]
+		.spacing(8)
+		.align_items(Alignment::Center);
+
+	let mut details = column![
+		header,
+		progress_bar(0.0..=1.0, entry.progress.fraction()).height(6),
+		text(status_text(entry)).size(12),
+	]
+	.spacing(4);
+
+	if let Some(outcome) = &entry.outcome {
+		for failure in outcome.failures.iter().take(MAX_SHOWN_FAILURES) {
+			details = details.push(
+				text(format!("{}: {}", failure.path.display(), failure.message))
+					.size(12)
+					.style(Color(iced::Color::from_rgb(0.9, 0.4, 0.4))),
+			);
+		}
+		if outcome.failures.len() > MAX_SHOWN_FAILURES {
+			details = details.push(text(format!("and {} more", outcome.failures.len() - MAX_SHOWN_FAILURES)).size(12));
+		}
+	}
+	details.into()
+}
+
+/// "12 MB of 40 MB, 5 of 20 files, 3 MB/s, 12s left" while running, how it ended afterwards
+fn status_text(entry: &JobEntry) -> String {
+	let progress = &entry.progress;
+	let mut parts = Vec::new();
+	if progress.bytes_total > 0 {
+		parts.push(format!("{} of {}", format_size(progress.bytes_done), format_size(progress.bytes_total)));
+	}
+	parts.push(format!("{} of {} files", progress.files_done, progress.files_total));
+
+	match &entry.outcome {
+		Some(outcome) => {
+			let ended = match outcome.cancelled {
+				true if entry.can_continue() => "Stopped, can be resumed",
+				true => "Cancelled",
+				false => "Done",
+			};
+			let failed = match outcome.failures.len() {
+				0 => String::new(),
+				n => format!(", {} failed", n),
+			};
+			// A job from before rsfiles was restarted did not count anything yet
+			if progress.files_total == 0 {
+				return format!("{}{}", ended, failed);
+			}
+			format!("{}{} ({})", ended, failed, parts.join(", "))
+		}
+		None => {
+			if let Some(speed) = progress.bytes_per_second() {
+				parts.push(format!("{}/s", format_size(speed as u64)));
+			}
+			if let Some(eta) = progress.eta() {
+				parts.push(format!("{} left", duration_text(eta)));
+			}
+			if let Some(name) = progress.current.as_ref().and_then(|path| path.file_name()) {
+				parts.push(name.to_string_lossy().into_owned());
+			}
+			let paused = if entry.control.is_paused() { "Paused, " } else { "" };
+			format!("{}{}", paused, parts.join(", "))
+		}
+	}
+}
+
+fn duration_text(duration: Duration) -> String {
+	let seconds = duration.as_secs();
+	match seconds {
+		0..60 => format!("{}s", seconds),
+		60..3600 => format!("{}m {}s", seconds / 60, seconds % 60),
+		_ => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+	}
+}
@@ -4,8 +4,10 @@ use iced::{Settings, Size, Application};
 mod file_manager;
 mod helper;
 mod popup;
-mod navigation;
+mod conflict;
 mod delete;
+mod jobs;
+mod selection;
 
 fn main() -> iced::Result {
 	file_manager::FileManager::run(Settings {
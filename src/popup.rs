@@ -7,6 +7,8 @@ use std::{fs, path::PathBuf};
 #[allow(dead_code)]
 pub struct PopupState {
     pub file_path: PathBuf,
+    // Everything the menu acts on, the clicked file included
+    pub selection: Vec<PathBuf>,
     pub position: Point,
 }
 
@@ -14,6 +16,9 @@ pub struct PopupState {
 #[allow(dead_code)]
 pub enum PopupMessage {
     CopyToClipboard(String),
+    Copy,
+    Cut,
+    Delete,
     ClosePopup,
     StartRename,
     RenameInputChanged(String),
@@ -104,16 +109,39 @@ impl Popup {
         }
     }
 
-    pub fn view(&self) -> Element<PopupMessage> {
-        let path = self.state.file_path.to_string_lossy().to_string();
+    pub fn view(&self) -> Element<'_, PopupMessage> {
+        let multiple = self.state.selection.len() > 1;
+        let paths = if multiple {
+            self.state.selection.iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("\n")
+        } else {
+            self.state.file_path.to_string_lossy().to_string()
+        };
         let is_dir = self.state.file_path.is_dir();
 
         let mut popup_buttons = vec![
-            button("Copy Path")
-                .on_press(PopupMessage::CopyToClipboard(path))
+            button(if multiple { "Copy Paths" } else { "Copy Path" })
+                .on_press(PopupMessage::CopyToClipboard(paths))
                 .padding([4, 8])
                 .style(iced::theme::Button::Secondary)
                 .into(),
+            button("Copy")
+                .on_press(PopupMessage::Copy)
+                .padding([4, 8])
+                .style(iced::theme::Button::Secondary)
+                .into(),
+            button("Cut")
+                .on_press(PopupMessage::Cut)
+                .padding([4, 8])
+                .style(iced::theme::Button::Secondary)
+                .into(),
+            button("Delete")
+                .on_press(PopupMessage::Delete)
+                .padding([4, 8])
+                .style(iced::theme::Button::Destructive)
+                .into(),
             button("Close")
                 .on_press(PopupMessage::ClosePopup)
                 .padding([4, 8])
@@ -121,7 +149,7 @@ impl Popup {
                 .into(),
         ];
 
-        if !self.renaming {
+        if !self.renaming && !multiple {
             popup_buttons.insert(
                 0,
                 button("Rename")
@@ -134,17 +162,24 @@ impl Popup {
 
         let popup_content = container(
             column![
-                text(format!("{}:", if is_dir { "Folder" } else { "File" }))
+                text(if multiple {
+                    format!("{} items:", self.state.selection.len())
+                } else {
+                    format!("{}:", if is_dir { "Folder" } else { "File" })
+                })
                     .style(iced::theme::Text::Color(iced::Color::from_rgb(
                         0.9, 0.9, 1.0
                     )))
                     .size(14),
-                text(
-                    &self.state.file_path
+                text(if multiple {
+                    String::new()
+                } else {
+                    self.state.file_path
                         .file_name()
                         .unwrap_or_default()
                         .to_string_lossy()
-                )
+                        .to_string()
+                })
                 .style(iced::theme::Text::Color(iced::Color::from_rgb(
                     0.7, 0.7, 0.8
                 )))
@@ -176,7 +211,7 @@ impl Popup {
                 } else {
                     column![].spacing(0)
                 },
-                row(popup_buttons).spacing(8)
+                column(popup_buttons).spacing(4)
             ]
             .spacing(8)
             .padding(12),
@@ -193,7 +228,7 @@ impl Popup {
 
 pub fn calculate_popup_position(click_position: Point, window_size: Size) -> Point {
     const POPUP_WIDTH: f32 = 200.0;
-    const POPUP_HEIGHT: f32 = 150.0;
+    const POPUP_HEIGHT: f32 = 280.0;
     const MARGIN: f32 = 10.0;
 
     let mut x = click_position.x;
@@ -216,7 +251,7 @@ pub fn calculate_popup_position(click_position: Point, window_size: Size) -> Poi
     Point::new(x, y)
 }
 
-struct PopupStyle;
+pub struct PopupStyle;
 
 impl iced::widget::container::StyleSheet for PopupStyle {
     type Style = iced::Theme;
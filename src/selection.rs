@@ -0,0 +1,147 @@
+use rsfiles_core::FileEntry;
+use std::{collections::HashMap, ops::Range, path::{Path, PathBuf}};
+
+/// Selected entries of the file list with the size each one adds to the total
+#[derive(Clone, Default)]
+pub struct Selection {
+	items: HashMap<PathBuf, u64>,
+	// Where Shift+click ranges start, the last entry clicked without Shift
+	anchor: Option<PathBuf>,
+}
+
+/// Size an entry adds to the selection total, folders only count once their size is known
+fn selected_size(file: &FileEntry) -> u64 {
+	if file.is_dir() {
+		file.dir_size().map_or(0, |size| size.bytes)
+	} else {
+		file.size()
+	}
+}
+
+impl Selection {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn contains(&self, path: &Path) -> bool {
+		self.items.contains_key(path)
+	}
+
+	pub fn len(&self) -> usize {
+		self.items.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.items.is_empty()
+	}
+
+	pub fn total_size(&self) -> u64 {
+		self.items.values().sum()
+	}
+
+	/// The selected entry when exactly one is selected
+	pub fn single(&self) -> Option<&PathBuf> {
+		if self.items.len() == 1 { self.items.keys().next() } else { None }
+	}
+
+	/// Selected paths in a stable order
+	pub fn paths(&self) -> Vec<PathBuf> {
+		let mut paths: Vec<PathBuf> = self.items.keys().cloned().collect();
+		paths.sort();
+		paths
+	}
+
+	pub fn clear(&mut self) {
+		self.items.clear();
+		self.anchor = None;
+	}
+
+	/// Plain click, only `file` stays selected
+	pub fn select_only(&mut self, file: &FileEntry) {
+		self.items.clear();
+		self.items.insert(file.path(), selected_size(file));
+		self.anchor = Some(file.path());
+	}
+
+	/// Ctrl+click, adds or removes `file` without touching the rest
+	pub fn toggle(&mut self, file: &FileEntry) {
+		let path = file.path();
+		if self.items.remove(&path).is_none() {
+			self.items.insert(path.clone(), selected_size(file));
+		}
+		self.anchor = Some(path);
+	}
+
+	/// Shift+click, selects everything between the anchor and `file` in the order of `files`.
+	/// With `add` (Ctrl+Shift) the range is added to the selection instead of replacing it.
+	pub fn select_range<'a>(&mut self, files: impl Iterator<Item = &'a FileEntry> + Clone, file: &FileEntry, add: bool) {
+		let end = files.clone().position(|f| f.as_path() == file.as_path());
+		let start = self.anchor.as_ref()
+			.and_then(|anchor| files.clone().position(|f| f.as_path() == anchor))
+			.or(end);
+		let (Some(start), Some(end)) = (start, end) else { return };
+
+		if !add {
+			self.items.clear();
+		}
+		for file in files.skip(start.min(end)).take(start.abs_diff(end) + 1) {
+			self.items.insert(file.path(), selected_size(file));
+		}
+		if self.anchor.is_none() {
+			self.anchor = Some(file.path());
+		}
+	}
+
+	pub fn select_all<'a>(&mut self, files: impl Iterator<Item = &'a FileEntry>) {
+		self.items = files.map(|file| (file.path(), selected_size(file))).collect();
+	}
+
+	/// Adds `files` to the selection, the anchor stays where it is
+	pub fn extend<'a>(&mut self, files: impl Iterator<Item = &'a FileEntry>) {
+		self.items.extend(files.map(|file| (file.path(), selected_size(file))));
+	}
+
+	/// Keeps the total right when a folder size comes in after the folder was selected
+	pub fn update_size(&mut self, file: &FileEntry) {
+		if let Some(size) = self.items.get_mut(file.as_path()) {
+			*size = selected_size(file);
+		}
+	}
+
+	pub fn rename(&mut self, from: &Path, to: PathBuf) {
+		if let Some(size) = self.items.remove(from) {
+			self.items.insert(to.clone(), size);
+		}
+		if self.anchor.as_deref() == Some(from) {
+			self.anchor = Some(to);
+		}
+	}
+
+	pub fn remove(&mut self, path: &Path) {
+		self.items.remove(path);
+		if self.anchor.as_deref() == Some(path) {
+			self.anchor = None;
+		}
+	}
+}
+
+/// Rubber band dragged over the file list. Rows span the whole width,
+/// so only the vertical extent decides which ones it crosses.
+#[derive(Clone)]
+pub struct Lasso {
+	/// Where the drag started and where the pointer is now, in list coordinates
+	pub start: f32,
+	pub end: f32,
+	/// Selection from before the drag (kept with Ctrl), the crossed rows are added to it
+	pub base: Selection,
+}
+
+impl Lasso {
+	/// Rows of height `row_height` the band crosses
+	pub fn rows(&self, row_height: f32, count: usize) -> Range<usize> {
+		let (top, bottom) = (self.start.min(self.end), self.start.max(self.end));
+		let first = ((top.max(0.0) / row_height) as usize).min(count);
+		let last = ((bottom.max(0.0) / row_height).ceil() as usize).min(count);
+		first..last.max(first)
+	}
+}